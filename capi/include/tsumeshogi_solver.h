@@ -0,0 +1,57 @@
+#ifndef TSUMESHOGI_SOLVER_H
+#define TSUMESHOGI_SOLVER_H
+
+#include <stddef.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/* Thread safety: once a handle is created, tsume_solver_submit, _poll, _wait,
+ * _cancel and _result may be called on it from any thread, concurrently.
+ * In particular one thread may block in tsume_solver_wait while another calls
+ * tsume_solver_cancel. tsume_solver_free must only be called when no other
+ * call on the same handle is in progress. */
+typedef struct TsumeSolver TsumeSolver;
+
+typedef enum {
+    TSUME_ERROR = -1,    /* invalid SFEN or timeout, busy handle, or null pointer */
+    TSUME_IDLE = 0,      /* nothing submitted yet */
+    TSUME_PENDING = 1,   /* still searching */
+    TSUME_MATE = 2,      /* mate found; moves are available */
+    TSUME_NO_MATE = 3,   /* proven no mate */
+    TSUME_TIMEOUT = 4,   /* time limit exceeded */
+    TSUME_CANCELED = 5   /* canceled by tsume_solver_cancel */
+} TsumeStatus;
+
+/* Creates a solver handle. Free it with tsume_solver_free. */
+TsumeSolver *tsume_solver_new(void);
+
+/* Cancels any running search, waits for it and frees the handle. */
+void tsume_solver_free(TsumeSolver *solver);
+
+/* Starts solving an SFEN (with or without the "sfen " prefix) in a background
+ * thread. A timeout of 0 or less means no limit. Returns TSUME_PENDING, or
+ * TSUME_ERROR if the SFEN is invalid, the timeout is NaN, infinite or too
+ * large, or a search is already running. */
+TsumeStatus tsume_solver_submit(TsumeSolver *solver, const char *sfen, double timeout_sec);
+
+/* Returns the current status without blocking. */
+TsumeStatus tsume_solver_poll(TsumeSolver *solver);
+
+/* Blocks until the running search finishes and returns its status. */
+TsumeStatus tsume_solver_wait(TsumeSolver *solver);
+
+/* Asks the running search to stop; its status becomes TSUME_CANCELED. */
+void tsume_solver_cancel(TsumeSolver *solver);
+
+/* Copies the solution as space-separated USI moves into buf (NUL-terminated,
+ * truncated to len - 1 bytes) and returns the full length excluding the NUL,
+ * like snprintf. The string is empty unless the status is TSUME_MATE. */
+size_t tsume_solver_result(TsumeSolver *solver, char *buf, size_t len);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* TSUMESHOGI_SOLVER_H */
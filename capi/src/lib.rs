@@ -0,0 +1,202 @@
+use shogi_core::{Move, PartialPosition, ToUsi};
+use shogi_usi_parser::FromUsi;
+use solver::implementations::{HashMapTable, YasaiPosition};
+use solver::{best_solution, CancelableSearcher, CanceledError};
+use std::ffi::{c_char, c_double, CStr};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+// include/tsumeshogi_solver.h と値を揃える
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TsumeStatus {
+    Error = -1,
+    Idle = 0,
+    Pending = 1,
+    Mate = 2,
+    NoMate = 3,
+    Timeout = 4,
+    Canceled = 5,
+}
+
+type SearchResult = Arc<Mutex<Option<Result<Vec<Move>, CanceledError>>>>;
+
+// どの関数も &TsumeSolver しか作らない。cancel_flag はハンドルの寿命の間差し替えず、
+// 探索ごとに変わる結果とスレッドは state の Mutex の中に置く
+pub struct TsumeSolver {
+    cancel_flag: Arc<AtomicBool>,
+    state: Mutex<State>,
+}
+
+#[derive(Default)]
+struct State {
+    result: SearchResult,
+    handle: Option<JoinHandle<()>>,
+    submitted: bool,
+}
+
+impl State {
+    fn status(&self) -> TsumeStatus {
+        if !self.submitted {
+            return TsumeStatus::Idle;
+        }
+        match &*self.result.lock().expect("poisoned") {
+            None => TsumeStatus::Pending,
+            Some(Ok(v)) if v.is_empty() => TsumeStatus::NoMate,
+            Some(Ok(_)) => TsumeStatus::Mate,
+            Some(Err(CanceledError::Canceled)) => TsumeStatus::Canceled,
+            Some(Err(_)) => TsumeStatus::Timeout,
+        }
+    }
+}
+
+impl TsumeSolver {
+    fn status(&self) -> TsumeStatus {
+        self.state.lock().expect("poisoned").status()
+    }
+    // 待っている間も poll や cancel が state を使えるよう、ロックを外してから join する
+    fn join(&self) {
+        let handle = self.state.lock().expect("poisoned").handle.take();
+        if let Some(handle) = handle {
+            handle.join().ok();
+        }
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn tsume_solver_new() -> *mut TsumeSolver {
+    Box::into_raw(Box::new(TsumeSolver {
+        cancel_flag: Arc::new(AtomicBool::new(false)),
+        state: Mutex::new(State::default()),
+    }))
+}
+
+/// # Safety
+///
+/// `solver` must be null or a pointer returned by `tsume_solver_new` that has not been freed,
+/// and no other call may be using it.
+#[no_mangle]
+pub unsafe extern "C" fn tsume_solver_free(solver: *mut TsumeSolver) {
+    if solver.is_null() {
+        return;
+    }
+    let solver = Box::from_raw(solver);
+    solver.cancel_flag.store(true, Ordering::Relaxed);
+    solver.join();
+}
+
+/// # Safety
+///
+/// `solver` must be a valid handle and `sfen` a NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn tsume_solver_submit(
+    solver: *mut TsumeSolver,
+    sfen: *const c_char,
+    timeout_sec: c_double,
+) -> TsumeStatus {
+    let (Some(solver), false) = (solver.as_ref(), sfen.is_null()) else {
+        return TsumeStatus::Error;
+    };
+    let Ok(sfen) = CStr::from_ptr(sfen).to_str() else {
+        return TsumeStatus::Error;
+    };
+    let sfen = sfen.trim();
+    let sfen = sfen.strip_prefix("sfen").map_or(sfen, str::trim_start);
+    let Ok(pos) = PartialPosition::from_usi(&format!("sfen {sfen}")) else {
+        return TsumeStatus::Error;
+    };
+    // 0 以下は無制限、NaN や Duration に収まらない値はエラー
+    let timeout = if timeout_sec.is_nan() {
+        return TsumeStatus::Error;
+    } else if timeout_sec > 0.0 {
+        match Duration::try_from_secs_f64(timeout_sec) {
+            Ok(timeout) => Some(timeout),
+            Err(_) => return TsumeStatus::Error,
+        }
+    } else {
+        None
+    };
+    let mut state = solver.state.lock().expect("poisoned");
+    if state.status() == TsumeStatus::Pending {
+        return TsumeStatus::Error;
+    }
+    // Pending でなければ前の探索は結果を書き終えているので、すぐに終わる
+    if let Some(handle) = state.handle.take() {
+        handle.join().ok();
+    }
+    solver.cancel_flag.store(false, Ordering::Relaxed);
+    state.result = SearchResult::default();
+    state.submitted = true;
+    let (cancel_flag, result) = (Arc::clone(&solver.cancel_flag), Arc::clone(&state.result));
+    state.handle = Some(thread::spawn(move || {
+        let mut searcher: CancelableSearcher<YasaiPosition, HashMapTable> =
+            CancelableSearcher::new(YasaiPosition::from(pos), timeout);
+        searcher.set_cancel_flag(cancel_flag);
+        let ret = searcher.dfpn_search().map(|_| best_solution(&mut searcher));
+        *result.lock().expect("poisoned") = Some(ret);
+    }));
+    TsumeStatus::Pending
+}
+
+/// # Safety
+///
+/// `solver` must be null or a valid handle.
+#[no_mangle]
+pub unsafe extern "C" fn tsume_solver_poll(solver: *mut TsumeSolver) -> TsumeStatus {
+    solver
+        .as_ref()
+        .map_or(TsumeStatus::Error, TsumeSolver::status)
+}
+
+/// # Safety
+///
+/// `solver` must be null or a valid handle.
+#[no_mangle]
+pub unsafe extern "C" fn tsume_solver_wait(solver: *mut TsumeSolver) -> TsumeStatus {
+    let Some(solver) = solver.as_ref() else {
+        return TsumeStatus::Error;
+    };
+    solver.join();
+    solver.status()
+}
+
+/// # Safety
+///
+/// `solver` must be null or a valid handle.
+#[no_mangle]
+pub unsafe extern "C" fn tsume_solver_cancel(solver: *mut TsumeSolver) {
+    if let Some(solver) = solver.as_ref() {
+        solver.cancel_flag.store(true, Ordering::Relaxed);
+    }
+}
+
+/// # Safety
+///
+/// `solver` must be a valid handle, and `buf` must be null or point to at least `len` writable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn tsume_solver_result(
+    solver: *mut TsumeSolver,
+    buf: *mut c_char,
+    len: usize,
+) -> usize {
+    let Some(solver) = solver.as_ref() else {
+        return 0;
+    };
+    let result = Arc::clone(&solver.state.lock().expect("poisoned").result);
+    let moves = match &*result.lock().expect("poisoned") {
+        Some(Ok(v)) => v
+            .iter()
+            .map(|m| m.to_usi_owned())
+            .collect::<Vec<_>>()
+            .join(" "),
+        _ => String::new(),
+    };
+    if !buf.is_null() && len > 0 {
+        let n = moves.len().min(len - 1);
+        std::ptr::copy_nonoverlapping(moves.as_ptr(), buf.cast(), n);
+        *buf.add(n) = 0;
+    }
+    moves.len()
+}
@@ -0,0 +1,37 @@
+// tests/solve.c をシステムの C コンパイラでビルドし、cdylib とリンクして実行する
+#[cfg(unix)]
+#[test]
+fn c_program() {
+    use std::path::PathBuf;
+    use std::process::Command;
+
+    let manifest_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    // target/<profile>/deps/c_api-xxxx から target/<profile> へ
+    let lib_dir = std::env::current_exe()
+        .expect("failed to get test executable")
+        .parent()
+        .and_then(|deps| deps.parent())
+        .expect("failed to find target directory")
+        .to_path_buf();
+    let exe = lib_dir.join("tsumeshogi_solver_capi_test");
+    let cc = std::env::var("CC").unwrap_or_else(|_| String::from("cc"));
+    let status = Command::new(cc)
+        .arg(manifest_dir.join("tests/solve.c"))
+        .arg("-I")
+        .arg(manifest_dir.join("include"))
+        .arg("-L")
+        .arg(&lib_dir)
+        .arg("-ltsumeshogi_solver_capi")
+        .arg("-pthread")
+        .arg("-o")
+        .arg(&exe)
+        .status()
+        .expect("failed to run C compiler");
+    assert!(status.success(), "failed to compile tests/solve.c");
+    let status = Command::new(&exe)
+        .env("LD_LIBRARY_PATH", &lib_dir)
+        .env("DYLD_LIBRARY_PATH", &lib_dir)
+        .status()
+        .expect("failed to run C test program");
+    assert!(status.success(), "C test program failed");
+}
@@ -0,0 +1,56 @@
+#include <math.h>
+#include <pthread.h>
+#include <stdio.h>
+#include <string.h>
+#include <time.h>
+#include "tsumeshogi_solver.h"
+
+#define CHECK(cond)                                                  \
+    do {                                                             \
+        if (!(cond)) {                                               \
+            fprintf(stderr, "%s:%d: %s\n", __FILE__, __LINE__, #cond); \
+            return 1;                                                \
+        }                                                            \
+    } while (0)
+
+/* A mate in 7 that solve_mates leaves out because it takes too long, so the
+ * search is still running when it is canceled. */
+#define LONG_PROBLEM "ln3kgRl/2s1g2p1/2ppppn1p/p5p2/6b2/P3P4/1+rPP1PP1P/1P4S2/LNSK1G1NL w GPbsp 50"
+
+static void *cancel_later(void *solver) {
+    struct timespec delay = {0, 200 * 1000 * 1000};
+    nanosleep(&delay, NULL);
+    tsume_solver_cancel(solver);
+    return NULL;
+}
+
+int main(void) {
+    char buf[64];
+    TsumeSolver *solver = tsume_solver_new();
+    CHECK(solver != NULL);
+    CHECK(tsume_solver_poll(solver) == TSUME_IDLE);
+    CHECK(tsume_solver_submit(solver, "invalid", 0) == TSUME_ERROR);
+    CHECK(tsume_solver_submit(solver, "9/9/3pp4/+r2k1p3/2L1+p4/2+R6/B8/B8/9 b 4g4s4n3l14p 1", INFINITY) == TSUME_ERROR);
+    CHECK(tsume_solver_submit(solver, "9/9/3pp4/+r2k1p3/2L1+p4/2+R6/B8/B8/9 b 4g4s4n3l14p 1", NAN) == TSUME_ERROR);
+    CHECK(tsume_solver_poll(solver) == TSUME_IDLE);
+
+    CHECK(tsume_solver_submit(solver, "9/9/3pp4/+r2k1p3/2L1+p4/2+R6/B8/B8/9 b 4g4s4n3l14p 1", 5.0) == TSUME_PENDING);
+    CHECK(tsume_solver_wait(solver) == TSUME_MATE);
+    CHECK(tsume_solver_result(solver, buf, sizeof(buf)) == strlen("7e7b+ N*8f 7f7c"));
+    CHECK(strcmp(buf, "7e7b+ N*8f 7f7c") == 0);
+    /* truncated like snprintf */
+    CHECK(tsume_solver_result(solver, buf, 6) == strlen("7e7b+ N*8f 7f7c"));
+    CHECK(strcmp(buf, "7e7b+") == 0);
+
+    /* cancel from another thread while this one blocks in wait */
+    pthread_t canceler;
+    CHECK(tsume_solver_submit(solver, LONG_PROBLEM, 0) == TSUME_PENDING);
+    CHECK(pthread_create(&canceler, NULL, cancel_later, solver) == 0);
+    CHECK(tsume_solver_wait(solver) == TSUME_CANCELED);
+    CHECK(pthread_join(canceler, NULL) == 0);
+    CHECK(tsume_solver_result(solver, buf, sizeof(buf)) == 0);
+    CHECK(buf[0] == '\0');
+
+    tsume_solver_free(solver);
+    return 0;
+}
@@ -1,6 +1,10 @@
 mod node;
 pub mod search;
+#[cfg(feature = "stats")]
+mod stats;
 mod traits;
 
 pub use node::Node;
+#[cfg(feature = "stats")]
+pub use stats::{SearchStats, Stats, TableStats};
 pub use traits::{Position, Table};
@@ -1,6 +1,6 @@
 use std::ops;
 
-#[derive(Clone, Copy, PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum Node {
     Or,
     And,
@@ -16,6 +16,9 @@ where
     fn look_up_hash(&self, key: &u64) -> (T::U, T::U);
     // ハッシュに記録
     fn put_in_hash(&mut self, key: u64, value: (T::U, T::U));
+    // 合法手を生成したノードとその子ノード数を集計する
+    #[cfg(feature = "stats")]
+    fn expanded(&mut self, _node: Node, _children: usize) {}
 
     // ルートでの反復深化
     fn dfpn_search(&mut self) {
@@ -42,6 +45,8 @@ where
         }
         // 2. 合法手の生成
         let children = self.generate_legal_moves(node);
+        #[cfg(feature = "stats")]
+        self.expanded(node, children.len());
         if children.is_empty() {
             // ?
             self.put_in_hash(hash, (T::U::max_value(), T::U::zero()));
@@ -0,0 +1,49 @@
+// `stats` feature を有効にしたときだけ集計する探索の統計
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SearchStats {
+    // mid で合法手を生成した回数
+    pub expansions: u64,
+    // ルートからの最大の深さ
+    pub max_depth: usize,
+    pub or_nodes: u64,
+    pub or_children: u64,
+    pub and_nodes: u64,
+    pub and_children: u64,
+}
+
+impl SearchStats {
+    // 展開した OR ノードあたりの子ノード数 (王手の数)
+    pub fn or_branching(&self) -> f64 {
+        ratio(self.or_children, self.or_nodes)
+    }
+    // 展開した AND ノードあたりの子ノード数 (応手の数)
+    pub fn and_branching(&self) -> f64 {
+        ratio(self.and_children, self.and_nodes)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TableStats {
+    pub lookups: u64,
+    pub hits: u64,
+    pub misses: u64,
+    // 別の局面の値を上書きした回数 (VecTable のみ)
+    pub overwrites: u64,
+    // 置換表を掃除した回数 (今の実装はどれも掃除しないので 0)
+    pub gc_runs: u64,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Stats {
+    pub search: SearchStats,
+    pub table: TableStats,
+}
+
+fn ratio(n: u64, d: u64) -> f64 {
+    if d == 0 {
+        0.0
+    } else {
+        n as f64 / d as f64
+    }
+}
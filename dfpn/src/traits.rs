@@ -1,4 +1,6 @@
 use crate::Node;
+#[cfg(feature = "stats")]
+use crate::TableStats;
 use num_traits::{PrimInt, SaturatingAdd, Unsigned};
 use shogi_core::Move;
 
@@ -16,4 +18,16 @@ pub trait Table: Default {
 
     fn look_up_hash(&self, key: &u64) -> (Self::U, Self::U);
     fn put_in_hash(&mut self, key: u64, value: (Self::U, Self::U));
+    // 格納している局面数 (進捗の表示用)
+    fn entries(&self) -> usize {
+        0
+    }
+    // 格納できる局面数の上限。上限が無ければ None
+    fn capacity(&self) -> Option<usize> {
+        None
+    }
+    #[cfg(feature = "stats")]
+    fn stats(&self) -> TableStats {
+        TableStats::default()
+    }
 }
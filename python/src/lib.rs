@@ -0,0 +1,133 @@
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use shogi_core::{PartialPosition, ToUsi};
+use shogi_usi_parser::FromUsi;
+use solver::implementations::{HashMapTable, YasaiPosition};
+use solver::{best_solution, CancelableSearcher, CanceledError};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant};
+
+#[pyclass(get_all)]
+#[derive(Clone)]
+struct SolveResult {
+    // "mate", "nomate", "timeout", "nodelimit"
+    status: String,
+    moves: Vec<String>,
+    length: usize,
+    nodes: u64,
+    elapsed: f64,
+}
+
+#[pymethods]
+impl SolveResult {
+    fn __repr__(&self) -> String {
+        format!(
+            "SolveResult(status={:?}, moves={:?}, length={}, nodes={}, elapsed={})",
+            self.status, self.moves, self.length, self.nodes, self.elapsed
+        )
+    }
+}
+
+fn parse(sfen: &str) -> PyResult<PartialPosition> {
+    let sfen = sfen.trim();
+    let sfen = sfen.strip_prefix("sfen").map_or(sfen, str::trim_start);
+    PartialPosition::from_usi(&format!("sfen {sfen}"))
+        .map_err(|e| PyValueError::new_err(format!("invalid sfen {sfen:?}: {e:?}")))
+}
+
+// 負の値や Duration に収まらない値は panic させずに ValueError にする
+fn timeout(timeout: Option<f64>) -> PyResult<Option<Duration>> {
+    timeout
+        .map(|secs| {
+            Duration::try_from_secs_f64(secs)
+                .map_err(|e| PyValueError::new_err(format!("invalid timeout {secs}: {e}")))
+        })
+        .transpose()
+}
+
+fn search(pos: PartialPosition, timeout: Option<Duration>, nodes: Option<u64>) -> SolveResult {
+    let now = Instant::now();
+    let mut searcher: CancelableSearcher<YasaiPosition, HashMapTable> =
+        CancelableSearcher::new(YasaiPosition::from(pos), timeout);
+    searcher.set_node_limit(nodes);
+    let result = searcher.dfpn_search().map(|_| best_solution(&mut searcher));
+    let (status, moves) = match result {
+        Ok(v) if v.is_empty() => ("nomate", v),
+        Ok(v) => ("mate", v),
+        Err(CanceledError::NodeLimit) => ("nodelimit", Vec::new()),
+        Err(_) => ("timeout", Vec::new()),
+    };
+    SolveResult {
+        status: status.to_string(),
+        length: moves.len(),
+        moves: moves.iter().map(|m| m.to_usi_owned()).collect(),
+        nodes: searcher.nodes(),
+        elapsed: now.elapsed().as_secs_f64(),
+    }
+}
+
+/// Solve a tsume problem given as SFEN. The GIL is released while searching.
+#[pyfunction]
+#[pyo3(signature = (sfen, timeout=None, nodes=None))]
+fn solve(
+    py: Python,
+    sfen: &str,
+    timeout: Option<f64>,
+    nodes: Option<u64>,
+) -> PyResult<SolveResult> {
+    let pos = parse(sfen)?;
+    let timeout = self::timeout(timeout)?;
+    Ok(py.allow_threads(|| search(pos, timeout, nodes)))
+}
+
+/// Solve many problems on `jobs` threads (default: number of CPUs) without holding the GIL.
+/// Results are returned in input order; `timeout` and `nodes` apply to each problem.
+#[pyfunction]
+#[pyo3(signature = (sfens, timeout=None, nodes=None, jobs=None))]
+fn solve_many(
+    py: Python,
+    sfens: Vec<String>,
+    timeout: Option<f64>,
+    nodes: Option<u64>,
+    jobs: Option<usize>,
+) -> PyResult<Vec<SolveResult>> {
+    let positions = sfens
+        .iter()
+        .map(|sfen| parse(sfen))
+        .collect::<PyResult<Vec<_>>>()?;
+    let timeout = self::timeout(timeout)?;
+    let jobs = jobs.unwrap_or_else(|| thread::available_parallelism().map_or(1, usize::from));
+    Ok(py.allow_threads(|| {
+        let next = AtomicUsize::new(0);
+        let results = Mutex::new(vec![None; positions.len()]);
+        thread::scope(|s| {
+            for _ in 0..jobs.max(1) {
+                s.spawn(|| loop {
+                    let i = next.fetch_add(1, Ordering::Relaxed);
+                    let Some(pos) = positions.get(i) else {
+                        break;
+                    };
+                    let result = search(pos.clone(), timeout, nodes);
+                    results.lock().expect("poisoned")[i] = Some(result);
+                });
+            }
+        });
+        results
+            .into_inner()
+            .expect("poisoned")
+            .into_iter()
+            .flatten()
+            .collect()
+    }))
+}
+
+#[pymodule]
+#[pyo3(name = "tsumeshogi_solver")]
+fn tsumeshogi_solver_python(_py: Python, m: &PyModule) -> PyResult<()> {
+    m.add_class::<SolveResult>()?;
+    m.add_function(wrap_pyfunction!(solve, m)?)?;
+    m.add_function(wrap_pyfunction!(solve_many, m)?)?;
+    Ok(())
+}
@@ -0,0 +1,33 @@
+import pytest
+import tsumeshogi_solver
+
+PROBLEM = "9/9/3pp4/+r2k1p3/2L1+p4/2+R6/B8/B8/9 b 4g4s4n3l14p 1"
+INITIAL = "lnsgkgsnl/1r5b1/ppppppppp/9/9/9/PPPPPPPPP/1B5R1/LNSGKGSNL b - 1"
+
+
+def test_solve():
+    result = tsumeshogi_solver.solve(PROBLEM, timeout=5)
+    assert result.status == "mate"
+    assert result.moves == ["7e7b+", "N*8f", "7f7c"]
+    assert result.length == 3
+    assert result.nodes > 0
+
+
+def test_no_mate():
+    result = tsumeshogi_solver.solve("sfen " + INITIAL)
+    assert result.status == "nomate"
+    assert result.moves == []
+
+
+def test_node_limit():
+    assert tsumeshogi_solver.solve(PROBLEM, nodes=1).status == "nodelimit"
+
+
+def test_invalid_sfen():
+    with pytest.raises(ValueError):
+        tsumeshogi_solver.solve("invalid")
+
+
+def test_solve_many():
+    results = tsumeshogi_solver.solve_many([PROBLEM, INITIAL] * 4, timeout=5, jobs=3)
+    assert [r.status for r in results] == ["mate", "nomate"] * 4
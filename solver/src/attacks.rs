@@ -0,0 +1,92 @@
+use shogi_core::{Color, PartialPosition, Piece, PieceKind, Square};
+
+// 駒の利き。ReferencePosition の指し手生成と、本体の certify の王手判定で共有する
+
+// color の玉に相手の駒が利いているか
+pub fn in_check(pos: &PartialPosition, color: Color) -> bool {
+    let king = Piece::new(PieceKind::King, color);
+    let Some(king_sq) = squares().find(|&sq| pos.piece_at(sq) == Some(king)) else {
+        return false;
+    };
+    squares().any(|from| match pos.piece_at(from) {
+        Some(piece) if piece.color() != color => {
+            targets(pos, from, piece.color(), piece.piece_kind()).contains(&king_sq)
+        }
+        _ => false,
+    })
+}
+
+const ORTHOGONAL: [(i8, i8); 4] = [(0, -1), (0, 1), (-1, 0), (1, 0)];
+const DIAGONAL: [(i8, i8); 4] = [(-1, -1), (1, -1), (-1, 1), (1, 1)];
+const GOLD: [(i8, i8); 6] = [(0, -1), (-1, -1), (1, -1), (-1, 0), (1, 0), (0, 1)];
+const SILVER: [(i8, i8); 5] = [(0, -1), (-1, -1), (1, -1), (-1, 1), (1, 1)];
+const KNIGHT: [(i8, i8); 2] = [(-1, -2), (1, -2)];
+const KING: [(i8, i8); 8] = [
+    (0, -1),
+    (0, 1),
+    (-1, 0),
+    (1, 0),
+    (-1, -1),
+    (1, -1),
+    (-1, 1),
+    (1, 1),
+];
+
+// 先手から見た向き (段が減る方が前) で、自駒のいない移動先を列挙する
+pub fn targets(pos: &PartialPosition, from: Square, color: Color, pk: PieceKind) -> Vec<Square> {
+    let (steps, slides): (&[(i8, i8)], &[(i8, i8)]) = match pk {
+        PieceKind::Pawn => (&[(0, -1)], &[]),
+        PieceKind::Lance => (&[], &[(0, -1)]),
+        PieceKind::Knight => (&KNIGHT, &[]),
+        PieceKind::Silver => (&SILVER, &[]),
+        PieceKind::Gold
+        | PieceKind::ProPawn
+        | PieceKind::ProLance
+        | PieceKind::ProKnight
+        | PieceKind::ProSilver => (&GOLD, &[]),
+        PieceKind::Bishop => (&[], &DIAGONAL),
+        PieceKind::Rook => (&[], &ORTHOGONAL),
+        PieceKind::ProBishop => (&ORTHOGONAL, &DIAGONAL),
+        PieceKind::ProRook => (&DIAGONAL, &ORTHOGONAL),
+        PieceKind::King => (&KING, &[]),
+    };
+    let sign = match color {
+        Color::Black => 1,
+        Color::White => -1,
+    };
+    let own = |sq: Square| pos.piece_at(sq).map(Piece::color) == Some(color);
+    let mut ret = Vec::new();
+    for &(df, dr) in steps {
+        if let Some(to) = offset(from, df * sign, dr * sign).filter(|&sq| !own(sq)) {
+            ret.push(to);
+        }
+    }
+    for &(df, dr) in slides {
+        let mut sq = from;
+        while let Some(next) = offset(sq, df * sign, dr * sign) {
+            if own(next) {
+                break;
+            }
+            ret.push(next);
+            if pos.piece_at(next).is_some() {
+                break;
+            }
+            sq = next;
+        }
+    }
+    ret
+}
+
+fn offset(sq: Square, df: i8, dr: i8) -> Option<Square> {
+    let file = sq.file() as i8 + df;
+    let rank = sq.rank() as i8 + dr;
+    if (1..=9).contains(&file) && (1..=9).contains(&rank) {
+        Square::new(file as u8, rank as u8)
+    } else {
+        None
+    }
+}
+
+pub fn squares() -> impl Iterator<Item = Square> {
+    (1..=9).flat_map(|file| (1..=9).filter_map(move |rank| Square::new(file, rank)))
+}
@@ -1,5 +1,7 @@
 mod cancelable_searcher;
+mod progress;
 mod search_or_cancel;
 
 pub use self::cancelable_searcher::*;
+pub use self::progress::{Observer, Progress};
 pub use self::search_or_cancel::SearchOrCancel;
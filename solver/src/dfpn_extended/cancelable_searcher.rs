@@ -1,7 +1,13 @@
-use super::SearchOrCancel;
+use super::{Observer, Progress, SearchOrCancel};
 use dfpn::search::Search;
 use dfpn::{Node, Position, Table};
+#[cfg(feature = "stats")]
+use dfpn::{SearchStats, Stats};
 use instant::Instant;
+use num_traits::ToPrimitive;
+use shogi_core::Move;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
 use std::time::Duration;
 use thiserror::Error;
 
@@ -9,6 +15,10 @@ use thiserror::Error;
 pub enum CanceledError {
     #[error("time limit exceeded")]
     Timeout,
+    #[error("node limit exceeded")]
+    NodeLimit,
+    #[error("canceled")]
+    Canceled,
 }
 
 pub struct CancelableSearcher<P, T> {
@@ -17,6 +27,18 @@ pub struct CancelableSearcher<P, T> {
     timeout: Option<Duration>,
     started: Instant,
     error: Option<CanceledError>,
+    nodes: u64,
+    node_limit: Option<u64>,
+    cancel_flag: Option<Arc<AtomicBool>>,
+    observer: Option<(Box<dyn Observer + Send>, Duration)>,
+    observed: Instant,
+    started_nodes: u64,
+    depth: usize,
+    // 展開中の手順。通知先がある間だけ記録する
+    path: Vec<Move>,
+    root: (u64, u64),
+    #[cfg(feature = "stats")]
+    stats: SearchStats,
 }
 
 impl<P, T> CancelableSearcher<P, T>
@@ -31,14 +53,98 @@ where
             timeout,
             started: Instant::now(),
             error: None,
+            nodes: 0,
+            node_limit: None,
+            cancel_flag: None,
+            observer: None,
+            observed: Instant::now(),
+            started_nodes: 0,
+            depth: 0,
+            path: Vec::new(),
+            root: (1, 1),
+            #[cfg(feature = "stats")]
+            stats: SearchStats::default(),
         }
     }
+    // 次の dfpn_search から適用される。置換表はそのまま残る
+    pub fn set_timeout(&mut self, timeout: Option<Duration>) {
+        self.timeout = timeout;
+    }
+    // 探索する局面数の上限 (これまでに探索した分も含む)
+    pub fn set_node_limit(&mut self, node_limit: Option<u64>) {
+        self.node_limit = node_limit;
+    }
+    // 別スレッドから true にすると探索を中断する
+    pub fn set_cancel_flag(&mut self, cancel_flag: Arc<AtomicBool>) {
+        self.cancel_flag = Some(cancel_flag);
+    }
+    // interval ごとと、dfpn_search の終了時に途中経過を通知する
+    pub fn set_observer<O>(&mut self, interval: Duration, observer: O)
+    where
+        O: Observer + Send + 'static,
+    {
+        self.observer = Some((Box::new(observer), interval));
+    }
+    pub fn nodes(&self) -> u64 {
+        self.nodes
+    }
+    // これまでの dfpn_search 全体の集計
+    #[cfg(feature = "stats")]
+    pub fn stats(&self) -> Stats {
+        Stats {
+            search: self.stats,
+            table: self.table.stats(),
+        }
+    }
+    pub fn progress(&self) -> Progress {
+        let elapsed = self.started.elapsed();
+        let nodes = self.nodes - self.started_nodes;
+        let nps = match elapsed.as_millis() as u64 {
+            0 => 0,
+            ms => nodes * 1000 / ms,
+        };
+        let entries = self.table.entries();
+        Progress {
+            nodes: self.nodes,
+            nps,
+            elapsed,
+            entries,
+            hashfull: self
+                .table
+                .capacity()
+                .filter(|&capacity| capacity > 0)
+                .map(|capacity| (entries * 1000 / capacity) as u32),
+            pn: self.root.0,
+            dn: self.root.1,
+            depth: self.depth,
+            path: self.path.clone(),
+        }
+    }
+    pub fn table(&self) -> &T {
+        &self.table
+    }
+    // 保存しておいた置換表に差し替えて、その続きから探索する
+    pub fn set_table(&mut self, table: T) {
+        self.table = table;
+    }
     pub fn dfpn_search(&mut self) -> Result<(), CanceledError> {
         self.started = Instant::now();
+        self.observed = self.started;
+        self.started_nodes = self.nodes;
         self.error = None;
         SearchOrCancel::dfpn_search(self);
+        self.notify();
         self.error.map_or(Ok(()), Result::Err)
     }
+    fn notify(&mut self) {
+        if self.observer.is_some() {
+            let progress = self.progress();
+            if let Some((observer, _)) = &mut self.observer {
+                observer.observe(&progress);
+            }
+            self.observed = Instant::now();
+        }
+    }
 }
 
 impl<P, T> Search<P, T> for CancelableSearcher<P, T>
@@ -53,9 +159,22 @@ where
         self.pos.generate_legal_moves(node)
     }
     fn do_move(&mut self, m: P::M) {
+        self.nodes += 1;
+        self.depth += 1;
+        if self.observer.is_some() {
+            self.path.push(m.into());
+        }
+        #[cfg(feature = "stats")]
+        {
+            self.stats.max_depth = self.stats.max_depth.max(self.depth);
+        }
         self.pos.do_move(m)
     }
     fn undo_move(&mut self, m: P::M) {
+        self.depth -= 1;
+        if self.observer.is_some() {
+            self.path.pop();
+        }
         self.pos.undo_move(m)
     }
     fn look_up_hash(&self, key: &u64) -> (T::U, T::U) {
@@ -64,6 +183,20 @@ where
     fn put_in_hash(&mut self, key: u64, value: (T::U, T::U)) {
         self.table.put_in_hash(key, value)
     }
+    #[cfg(feature = "stats")]
+    fn expanded(&mut self, node: Node, children: usize) {
+        self.stats.expansions += 1;
+        match node {
+            Node::Or => {
+                self.stats.or_nodes += 1;
+                self.stats.or_children += children as u64;
+            }
+            Node::And => {
+                self.stats.and_nodes += 1;
+                self.stats.and_children += children as u64;
+            }
+        }
+    }
 }
 
 impl<P, T> SearchOrCancel<P, T> for CancelableSearcher<P, T>
@@ -77,8 +210,31 @@ where
                 self.error = Some(CanceledError::Timeout);
             }
         }
+        if let Some(node_limit) = self.node_limit {
+            if self.nodes > node_limit {
+                self.error = Some(CanceledError::NodeLimit);
+            }
+        }
+        if let Some(cancel_flag) = &self.cancel_flag {
+            if cancel_flag.load(Ordering::Relaxed) {
+                self.error = Some(CanceledError::Canceled);
+            }
+        }
+        if let Some((_, interval)) = &self.observer {
+            if self.observed.elapsed() >= *interval {
+                self.notify();
+            }
+        }
         self.error.is_some()
     }
+    fn visit(&mut self, pn: T::U, dn: T::U) {
+        if self.depth == 0 {
+            self.root = (
+                pn.to_u64().unwrap_or(u64::MAX),
+                dn.to_u64().unwrap_or(u64::MAX),
+            );
+        }
+    }
 }
 
 #[cfg(test)]
@@ -147,4 +303,59 @@ mod tests {
             _ => panic!("expected timeout"),
         }
     }
+
+    #[test]
+    fn node_limit() {
+        let mut searcher: CancelableSearcher<_, HashMapTable> =
+            CancelableSearcher::new(InfinityPosition(M(1)), None);
+        searcher.set_node_limit(Some(1000));
+        match searcher.dfpn_search() {
+            Err(CanceledError::NodeLimit) => {}
+            _ => panic!("expected node limit"),
+        }
+    }
+
+    #[test]
+    fn observer() {
+        let mut searcher: CancelableSearcher<_, HashMapTable> =
+            CancelableSearcher::new(InfinityPosition(M(1)), None);
+        searcher.set_node_limit(Some(1000));
+        let (tx, rx) = std::sync::mpsc::channel();
+        searcher.set_observer(Duration::ZERO, move |progress: &Progress| {
+            tx.send((progress.nodes, progress.depth, progress.path.len()))
+                .expect("failed to send");
+        });
+        assert!(searcher.dfpn_search().is_err());
+        let reports = rx.try_iter().collect::<Vec<_>>();
+        assert!(reports.len() > 1);
+        assert!(reports.iter().all(|&(_, depth, len)| depth == len));
+        // 最後の通知は探索終了時のもの
+        assert_eq!(reports.last().map(|r| (r.0, r.1)), Some((1001, 0)));
+    }
+
+    #[cfg(feature = "stats")]
+    #[test]
+    fn stats() {
+        let mut searcher: CancelableSearcher<_, HashMapTable> =
+            CancelableSearcher::new(InfinityPosition(M(1)), None);
+        searcher.set_node_limit(Some(1000));
+        assert!(searcher.dfpn_search().is_err());
+        let stats = searcher.stats().search;
+        assert!(stats.expansions > 0);
+        assert_eq!(stats.or_children, stats.or_nodes * 2);
+        assert_eq!(stats.and_children, stats.and_nodes * 2);
+        assert!(stats.max_depth > 0);
+    }
+
+    #[test]
+    fn cancel_flag() {
+        let mut searcher: CancelableSearcher<_, HashMapTable> =
+            CancelableSearcher::new(InfinityPosition(M(1)), None);
+        let cancel_flag = Arc::new(AtomicBool::new(true));
+        searcher.set_cancel_flag(Arc::clone(&cancel_flag));
+        match searcher.dfpn_search() {
+            Err(CanceledError::Canceled) => {}
+            _ => panic!("expected cancel"),
+        }
+    }
 }
@@ -0,0 +1,34 @@
+use shogi_core::Move;
+use std::time::Duration;
+
+// 探索中に定期的に通知される途中経過
+#[derive(Debug, Clone)]
+pub struct Progress {
+    pub nodes: u64,
+    pub nps: u64,
+    // 今回の dfpn_search を始めてからの時間
+    pub elapsed: Duration,
+    pub entries: usize,
+    // 置換表の使用率 (千分率)。容量に上限が無ければ None
+    pub hashfull: Option<u32>,
+    // ルートの証明数・反証数 (ルートで最後に反復したときの値)
+    pub pn: u64,
+    pub dn: u64,
+    pub depth: usize,
+    // ルートから現在展開中のノードまでの手順 (最も証明しやすい経路)。
+    // 探索を遅くしないよう、通知先が設定されている間だけ記録する (それ以外は空)
+    pub path: Vec<Move>,
+}
+
+pub trait Observer {
+    fn observe(&mut self, progress: &Progress);
+}
+
+impl<F> Observer for F
+where
+    F: FnMut(&Progress),
+{
+    fn observe(&mut self, progress: &Progress) {
+        self(progress)
+    }
+}
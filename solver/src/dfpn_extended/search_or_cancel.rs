@@ -8,6 +8,8 @@ where
     T: Table,
 {
     fn cancel(&mut self) -> bool;
+    // 展開中のノードの証明数・反証数を、反復のたびに通知する
+    fn visit(&mut self, _pn: T::U, _dn: T::U) {}
 
     // ルートでの反復深化
     fn dfpn_search(&mut self) {
@@ -35,6 +37,8 @@ where
         }
         // 2. 合法手の生成
         let children = self.generate_legal_moves(node);
+        #[cfg(feature = "stats")]
+        self.expanded(node, children.len());
         if children.is_empty() {
             // ?
             self.put_in_hash(hash, (T::U::max_value(), T::U::zero()));
@@ -54,6 +58,10 @@ where
             } else {
                 self.min_delta(&children)
             };
+            match node {
+                Node::Or => self.visit(md, sp),
+                Node::And => self.visit(sp, md),
+            }
             if phi <= md || delta <= sp {
                 self.put_in_hash(hash, (md, sp));
                 return match node {
@@ -79,6 +87,17 @@ where
             SearchOrCancel::mid(self, h, phi_n_c, delta_n_c, !node);
             self.undo_move(m);
         }
-        (T::U::zero(), T::U::zero())
+        // 中断時は 3. で置いた値を途中経過の証明数・反証数で上書きし、続きから探索できるようにする
+        let sp = self.sum_phi(&children);
+        let md = if sp >= T::U::max_value() - T::U::one() {
+            T::U::zero()
+        } else {
+            self.min_delta(&children)
+        };
+        self.put_in_hash(hash, (md, sp));
+        match node {
+            Node::Or => (md, sp),
+            Node::And => (sp, md),
+        }
     }
 }
@@ -0,0 +1,50 @@
+use crate::dfpn_extended::{CancelableSearcher, CanceledError};
+use crate::solve::{best_solution, CalculateResult};
+use dfpn::{Position, Table};
+use shogi_core::{Move, PartialPosition};
+use std::time::Duration;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Hint {
+    pub moves: Vec<Move>,
+    // ヒントの手順の後、詰みまでの残り手数 (不詰なら 0)
+    pub remaining: usize,
+}
+
+// 一度の dfpn_search で得た置換表と手順を使い回し、2 回目以降のヒントは探索しない
+pub struct Hinter<P, T> {
+    searcher: CancelableSearcher<P, T>,
+    solution: Option<Vec<Move>>,
+}
+
+impl<P, T> Hinter<P, T>
+where
+    P: Position + From<PartialPosition> + CalculateResult,
+    T: Table,
+{
+    pub fn new(position: PartialPosition, timeout: Option<Duration>) -> Self {
+        Self {
+            searcher: CancelableSearcher::new(P::from(position), timeout),
+            solution: None,
+        }
+    }
+    pub fn nodes(&self) -> u64 {
+        self.searcher.nodes()
+    }
+    // 時間切れの場合も置換表は残るので、再度呼べば続きから探索する
+    pub fn solution(&mut self) -> Result<&[Move], CanceledError> {
+        if self.solution.is_none() {
+            self.searcher.dfpn_search()?;
+            self.solution = Some(best_solution(&mut self.searcher));
+        }
+        Ok(self.solution.as_deref().unwrap_or_default())
+    }
+    pub fn hint(&mut self, plies: usize) -> Result<Hint, CanceledError> {
+        let solution = self.solution()?;
+        let len = plies.min(solution.len());
+        Ok(Hint {
+            moves: solution[..len].to_vec(),
+            remaining: solution.len() - len,
+        })
+    }
+}
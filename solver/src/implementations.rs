@@ -1,7 +1,12 @@
 mod hashmap_table;
+mod reference_position;
+mod result;
+#[cfg(feature = "stats")]
+mod table_stats;
 mod vec_table;
 mod yasai_position;
 
 pub use self::hashmap_table::HashMapTable;
+pub use self::reference_position::ReferencePosition;
 pub use self::vec_table::VecTable;
 pub use self::yasai_position::YasaiPosition;
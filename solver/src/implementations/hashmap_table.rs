@@ -1,16 +1,49 @@
+#[cfg(feature = "stats")]
+use super::table_stats::Counter;
 use dfpn::Table;
+#[cfg(feature = "stats")]
+use dfpn::TableStats;
 use num_traits::{PrimInt, SaturatingAdd, Unsigned};
 use std::collections::HashMap;
 
 #[derive(Default)]
 pub struct HashMapTable<U = u32> {
     table: HashMap<u64, (U, U)>,
+    #[cfg(feature = "stats")]
+    counter: Counter,
 }
 
 impl<U> HashMapTable<U> {
     pub fn new() -> Self {
         Self {
             table: HashMap::new(),
+            #[cfg(feature = "stats")]
+            counter: Counter::default(),
+        }
+    }
+}
+
+impl<U> HashMapTable<U>
+where
+    U: Copy,
+{
+    pub fn len(&self) -> usize {
+        self.table.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.table.is_empty()
+    }
+    pub fn iter(&self) -> impl Iterator<Item = (u64, (U, U))> + '_ {
+        self.table.iter().map(|(&key, &value)| (key, value))
+    }
+}
+
+impl<U> FromIterator<(u64, (U, U))> for HashMapTable<U> {
+    fn from_iter<I: IntoIterator<Item = (u64, (U, U))>>(iter: I) -> Self {
+        Self {
+            table: iter.into_iter().collect(),
+            #[cfg(feature = "stats")]
+            counter: Counter::default(),
         }
     }
 }
@@ -22,7 +55,16 @@ where
     type U = U;
 
     fn look_up_hash(&self, key: &u64) -> (U, U) {
-        if let Some(&v) = self.table.get(key) {
+        let v = self.table.get(key);
+        #[cfg(feature = "stats")]
+        self.counter.count(|stats| {
+            stats.lookups += 1;
+            match v {
+                Some(_) => stats.hits += 1,
+                None => stats.misses += 1,
+            }
+        });
+        if let Some(&v) = v {
             v
         } else {
             (Self::U::one(), Self::U::one())
@@ -31,4 +73,11 @@ where
     fn put_in_hash(&mut self, key: u64, value: (U, U)) {
         self.table.insert(key, value);
     }
+    fn entries(&self) -> usize {
+        self.table.len()
+    }
+    #[cfg(feature = "stats")]
+    fn stats(&self) -> TableStats {
+        self.counter.get()
+    }
 }
@@ -0,0 +1,277 @@
+use super::result;
+use crate::attacks::{in_check, squares, targets};
+use crate::solve::CalculateResult;
+use dfpn::Node;
+use shogi_core::{Color, Hand, Move, PartialPosition, Piece, PieceKind, Square};
+
+// yasai を使わず、盤面を毎回走査して指し手を生成する参照実装。
+// 遅いが単純なので、YasaiPosition との差分テストに使う
+pub struct ReferencePosition {
+    pos: PartialPosition,
+    history: Vec<PartialPosition>,
+}
+
+impl From<PartialPosition> for ReferencePosition {
+    fn from(pos: PartialPosition) -> Self {
+        Self {
+            pos,
+            history: Vec::new(),
+        }
+    }
+}
+
+impl dfpn::Position for ReferencePosition {
+    type M = Move;
+
+    fn hash_key(&self) -> u64 {
+        hash_key(&self.pos)
+    }
+    fn generate_legal_moves(&mut self, node: Node) -> Vec<(Self::M, u64)> {
+        let mut children = Vec::new();
+        for m in legal_moves(&self.pos, true) {
+            let mut next = self.pos.clone();
+            next.make_move(m);
+            if node == Node::And || in_check(&next, next.side_to_move()) {
+                children.push((m, hash_key(&next)));
+            }
+        }
+        children
+    }
+    fn do_move(&mut self, m: Self::M) {
+        self.history.push(self.pos.clone());
+        self.pos.make_move(m);
+    }
+    fn undo_move(&mut self, _m: Self::M) {
+        self.pos = self.history.pop().expect("no move to undo");
+    }
+}
+
+impl CalculateResult for ReferencePosition {
+    fn calculate_result_and_score(&self, moves: &[Move]) -> (Vec<Move>, usize) {
+        let hand = self.pos.hand_of_a_player(self.pos.side_to_move().flip());
+        result::calculate_result_and_score(hand, moves)
+    }
+}
+
+// 打ち歩詰めの判定中は、その応手の中の打ち歩詰めまでは調べない
+fn legal_moves(pos: &PartialPosition, drop_pawn_mate: bool) -> Vec<Move> {
+    let color = pos.side_to_move();
+    let mut moves = Vec::new();
+    for from in squares() {
+        let Some(piece) = pos.piece_at(from) else {
+            continue;
+        };
+        if piece.color() != color {
+            continue;
+        }
+        let pk = piece.piece_kind();
+        for to in targets(pos, from, color, pk) {
+            let promotable = pk.promote().is_some()
+                && (relative_rank(from, color) <= 3 || relative_rank(to, color) <= 3);
+            if promotable {
+                moves.push(Move::Normal {
+                    from,
+                    to,
+                    promote: true,
+                });
+            }
+            if !must_promote(pk, relative_rank(to, color)) {
+                moves.push(Move::Normal {
+                    from,
+                    to,
+                    promote: false,
+                });
+            }
+        }
+    }
+    let hand = pos.hand_of_a_player(color);
+    for pk in Hand::all_hand_pieces() {
+        if hand.count(pk).unwrap_or_default() == 0 {
+            continue;
+        }
+        for to in squares() {
+            if pos.piece_at(to).is_some() || must_promote(pk, relative_rank(to, color)) {
+                continue;
+            }
+            // 二歩
+            if pk == PieceKind::Pawn
+                && (1..=9).any(|rank| {
+                    Square::new(to.file(), rank).and_then(|sq| pos.piece_at(sq))
+                        == Some(Piece::new(PieceKind::Pawn, color))
+                })
+            {
+                continue;
+            }
+            moves.push(Move::Drop {
+                piece: Piece::new(pk, color),
+                to,
+            });
+        }
+    }
+    moves.retain(|&m| {
+        let mut next = pos.clone();
+        next.make_move(m);
+        if in_check(&next, color) {
+            return false;
+        }
+        // 打ち歩詰め
+        let pawn_drop =
+            matches!(m, Move::Drop { piece, .. } if piece.piece_kind() == PieceKind::Pawn);
+        !(drop_pawn_mate
+            && pawn_drop
+            && in_check(&next, color.flip())
+            && legal_moves(&next, false).is_empty())
+    });
+    moves
+}
+
+// 行き所のない駒になる段か
+fn must_promote(pk: PieceKind, rank: u8) -> bool {
+    match pk {
+        PieceKind::Pawn | PieceKind::Lance => rank == 1,
+        PieceKind::Knight => rank <= 2,
+        _ => false,
+    }
+}
+
+// 手番側から見た段 (敵陣が 1..=3)
+fn relative_rank(sq: Square, color: Color) -> u8 {
+    match color {
+        Color::Black => sq.rank(),
+        Color::White => 10 - sq.rank(),
+    }
+}
+
+// Zobrist hashing。乱数表は splitmix64 で決め打ちに生成し、ハッシュ値は局面から毎回計算し直す
+struct Zobrist {
+    board: [[u64; 28]; 81],
+    hand: [[[u64; 19]; 7]; 2],
+    side: u64,
+}
+
+const fn splitmix64(state: u64) -> (u64, u64) {
+    let state = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    let mut z = state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    (state, z ^ (z >> 31))
+}
+
+static ZOBRIST: Zobrist = {
+    let mut zobrist = Zobrist {
+        board: [[0; 28]; 81],
+        hand: [[[0; 19]; 7]; 2],
+        side: 0,
+    };
+    let mut state = 0;
+    let mut i = 0;
+    while i < 81 {
+        let mut j = 0;
+        while j < 28 {
+            let (next, key) = splitmix64(state);
+            zobrist.board[i][j] = key;
+            state = next;
+            j += 1;
+        }
+        i += 1;
+    }
+    let mut c = 0;
+    while c < 2 {
+        let mut k = 0;
+        while k < 7 {
+            // 持駒 0 枚は 0 にしておく
+            let mut n = 1;
+            while n < 19 {
+                let (next, key) = splitmix64(state);
+                zobrist.hand[c][k][n] = key;
+                state = next;
+                n += 1;
+            }
+            k += 1;
+        }
+        c += 1;
+    }
+    zobrist.side = splitmix64(state).1;
+    zobrist
+};
+
+fn hash_key(pos: &PartialPosition) -> u64 {
+    let mut key = 0;
+    for sq in squares() {
+        if let Some(piece) = pos.piece_at(sq) {
+            key ^= ZOBRIST.board[sq.array_index()][piece_index(piece)];
+        }
+    }
+    for color in [Color::Black, Color::White] {
+        let hand = pos.hand_of_a_player(color);
+        for (k, pk) in Hand::all_hand_pieces().enumerate() {
+            let n = hand.count(pk).unwrap_or_default() as usize;
+            key ^= ZOBRIST.hand[color_index(color)][k][n];
+        }
+    }
+    if pos.side_to_move() == Color::White {
+        key ^= ZOBRIST.side;
+    }
+    key
+}
+
+fn color_index(color: Color) -> usize {
+    match color {
+        Color::Black => 0,
+        Color::White => 1,
+    }
+}
+
+fn piece_index(piece: Piece) -> usize {
+    let kind = match piece.piece_kind() {
+        PieceKind::Pawn => 0,
+        PieceKind::Lance => 1,
+        PieceKind::Knight => 2,
+        PieceKind::Silver => 3,
+        PieceKind::Gold => 4,
+        PieceKind::Bishop => 5,
+        PieceKind::Rook => 6,
+        PieceKind::King => 7,
+        PieceKind::ProPawn => 8,
+        PieceKind::ProLance => 9,
+        PieceKind::ProKnight => 10,
+        PieceKind::ProSilver => 11,
+        PieceKind::ProBishop => 12,
+        PieceKind::ProRook => 13,
+    };
+    color_index(piece.color()) * 14 + kind
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use shogi_usi_parser::FromUsi;
+
+    #[test]
+    fn legal_moves_of_initial_position() {
+        let pos = PartialPosition::from_usi(
+            "sfen lnsgkgsnl/1r5b1/ppppppppp/9/9/9/PPPPPPPPP/1B5R1/LNSGKGSNL b - 1",
+        )
+        .expect("failed to parse sfen");
+        assert_eq!(legal_moves(&pos, true).len(), 30);
+        let mut pos = ReferencePosition::from(pos);
+        assert!(dfpn::Position::generate_legal_moves(&mut pos, Node::Or).is_empty());
+    }
+
+    #[test]
+    fn drop_pawn_mate() {
+        // ▲1二歩打は打ち歩詰め、▲1二香打は詰み
+        let pos = PartialPosition::from_usi("sfen 7lk/7l1/8G/9/9/9/9/9/9 b PL 1")
+            .expect("failed to parse sfen");
+        let drops = legal_moves(&pos, true)
+            .into_iter()
+            .filter_map(|m| match m {
+                Move::Drop { piece, to } if to == Square::new(1, 2).expect("invalid square") => {
+                    Some(piece.piece_kind())
+                }
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(drops, vec![PieceKind::Lance]);
+    }
+}
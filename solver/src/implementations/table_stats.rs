@@ -0,0 +1,17 @@
+use dfpn::TableStats;
+use std::cell::Cell;
+
+// look_up_hash は &self なので、集計は Cell 越しに行う
+#[derive(Default)]
+pub(crate) struct Counter(Cell<TableStats>);
+
+impl Counter {
+    pub(crate) fn count(&self, f: impl FnOnce(&mut TableStats)) {
+        let mut stats = self.0.get();
+        f(&mut stats);
+        self.0.set(stats);
+    }
+    pub(crate) fn get(&self) -> TableStats {
+        self.0.get()
+    }
+}
@@ -1,9 +1,19 @@
+#[cfg(feature = "stats")]
+use super::table_stats::Counter;
 use dfpn::Table;
+#[cfg(feature = "stats")]
+use dfpn::TableStats;
 use num_traits::{PrimInt, SaturatingAdd, Unsigned};
 
 pub struct VecTable<U = u32> {
     table: Vec<Option<(U, U)>>,
     mask: usize,
+    used: usize,
+    // 値と別に持つハッシュ。衝突と上書きの集計にだけ使う
+    #[cfg(feature = "stats")]
+    keys: Vec<u64>,
+    #[cfg(feature = "stats")]
+    counter: Counter,
 }
 
 impl<U> VecTable<U>
@@ -14,6 +24,11 @@ where
         Self {
             table: vec![None; 1 << bits],
             mask: (1 << bits) - 1,
+            used: 0,
+            #[cfg(feature = "stats")]
+            keys: vec![0; 1 << bits],
+            #[cfg(feature = "stats")]
+            counter: Counter::default(),
         }
     }
 }
@@ -34,9 +49,41 @@ where
     type U = U;
 
     fn look_up_hash(&self, key: &u64) -> (U, U) {
-        self.table[(*key as usize) & self.mask].unwrap_or((Self::U::one(), Self::U::one()))
+        let index = (*key as usize) & self.mask;
+        #[cfg(feature = "stats")]
+        self.counter.count(|stats| {
+            stats.lookups += 1;
+            // 別の局面の値が返る場合もヒットではなくミスとして数える
+            match self.table[index] {
+                Some(_) if self.keys[index] == *key => stats.hits += 1,
+                _ => stats.misses += 1,
+            }
+        });
+        self.table[index].unwrap_or((Self::U::one(), Self::U::one()))
     }
     fn put_in_hash(&mut self, key: u64, value: (U, U)) {
-        self.table[(key as usize) & self.mask] = Some(value);
+        let index = (key as usize) & self.mask;
+        #[cfg(feature = "stats")]
+        {
+            if self.table[index].is_some() && self.keys[index] != key {
+                self.counter.count(|stats| stats.overwrites += 1);
+            }
+            self.keys[index] = key;
+        }
+        let entry = &mut self.table[index];
+        if entry.is_none() {
+            self.used += 1;
+        }
+        *entry = Some(value);
+    }
+    fn entries(&self) -> usize {
+        self.used
+    }
+    fn capacity(&self) -> Option<usize> {
+        Some(self.table.len())
+    }
+    #[cfg(feature = "stats")]
+    fn stats(&self) -> TableStats {
+        self.counter.get()
     }
 }
@@ -1,56 +1,87 @@
+pub mod attacks;
 mod dfpn_extended;
+mod hint;
 pub mod implementations;
+mod persist;
+mod proof_tree;
+mod refutation;
+mod regression;
+mod resume;
 mod solve;
+mod verify;
 
+pub use self::dfpn_extended::{CancelableSearcher, CanceledError, Observer, Progress};
+pub use self::hint::*;
+pub use self::persist::*;
+pub use self::proof_tree::*;
+pub use self::refutation::*;
+pub use self::regression::*;
+pub use self::resume::*;
 pub use self::solve::*;
+pub use self::verify::*;
+#[cfg(feature = "stats")]
+pub use dfpn::{SearchStats, Stats, TableStats};
 
 #[cfg(test)]
 mod tests {
-    use super::implementations::{HashMapTable, YasaiPosition};
-    use super::solve;
-    use shogi_core::PartialPosition;
+    use super::implementations::{HashMapTable, ReferencePosition, YasaiPosition};
+    use super::{
+        best_solution, compare_positions, is_mating_move, proof_tree, read_table, refute, solve,
+        verify_case, verify_solution, write_table, CancelableSearcher, CanceledError, FlawKind,
+        Hinter, RegressionCase, Resumable, TableFileError, TreeLimits,
+    };
+    use shogi_core::{Move, PartialPosition};
     use shogi_usi_parser::FromUsi;
     use std::time::Duration;
 
+    // https://yaneuraou.yaneu.com/2020/12/25/christmas-present/
+    // solve_mates と differential_mates で共有する
+    const MATES: &[&str] = &[
+        // head -10 mate3.sfen
+        "ln1gkg1nl/6+P2/2sppps1p/2p3p2/p8/P1P1P3P/2NP1PP2/3s1KSR1/L1+b2G1NL w R2Pbgp 42",
+        "l3kgsnl/9/p1pS+Bp3/7pp/6PP1/9/PPPPPPn1P/1B1GG2+r1/LNS1K3L w RG3Psnp 54",
+        "l3k2nl/4g1gb1/1+S1pspp+P1/p1p6/3n4p/2PPR1P2/P2bPP2P/5GS2/LN1K4L w R2Pgsn2p 50",
+        "lns+R4l/1p1p5/p1pkppB1p/6p2/1R7/6P1P/P1PPnPS2/2+b1G1g2/L3K1sNL b 2GS3Pnp 51",
+        "1+P1gkg2l/2s3s+P1/3ppp2p/P1p2npp1/l2N1+b3/3KP1P2/N2P1PS1P/2+p1G2R1/L1+r3sNL w Pbgp 58",
+        "lnsG5/4g4/prpp1p1pp/1p4p2/4+B3k/2P1P4/P+b1PSP1LP/4K2SL/2G2G1r1 b SP3nl3p 71",
+        "l5+R1l/4kS3/p4pnpp/2Pppb3/6p1P/P2s5/NP2+nPPR1/2+bS2GK1/L6NL b 3GSP4p 93",
+        "lR5nl/5k1b1/2gp3p1/2s1p1P2/p4N2p/P3PpR2/1PPP1P2P/2G1K2s1/LN6L b GSN2Pbgs2p 83",
+        "l1+R5l/2pS5/p2pp+P1pp/2k3p2/2N4P1/PP2R1P1P/2+pPP1N2/2GSG1bs1/LN1K4L b 2GSNPbp 73",
+        "lnsg4l/1r1b5/p1pp1+N1+R1/4p3p/9/P3SSk2/NpPPPPg1P/2GK5/L1S4NL b 2Pbg4p 91",
+        // head -10 mate5.sfen
+        "l2gkg2l/2s3s2/p1nppp1pp/2p3p2/P4P1P1/4n3P/1PPPG1N2/1BKS2+s2/LN3+r3 w RBgl3p 72",
+        "lnsgs2+Pl/3kg4/p1pppN2p/6pp1/9/7R1/P1PP1Sg1P/1S3+b3/LN5KL w Nrbg6p 58",
+        "lnG4nl/5k3/p1p+R1g1p1/1p1p3sp/5N3/2P1p1p2/PP1GP3P/1SG2+p1+b1/LN1K4L w Srbs4p 60",
+        "ln4knl/4+N2b1/4ppsG1/p1P5p/2G3pp1/3P1P2P/P2+pP1P2/2+srSK3/L+r3G1NL w G4Pbs 78",
+        "ln5+Pl/3s1kg+R1/p2ppl2p/2ps1Bp2/P8/2P3P1P/N2gP4/5KS2/L+r3G1N+b b GS3Pn3p 57",
+        "l3k3l/1r1sg1B2/3p2+R1p/2p1pN2P/9/pPP1PP3/3PK1P2/2G1sg3/LNS2+n1NL b 5Pbgsp 69",
+        "ln1g4l/2s2+R3/2kp2G2/p1r2pp1p/4S4/Pp1Gp1P2/BP1P4P/2GKs1+bP1/LNN5L w SN4Pp 88",
+        "ln1+P1GBnl/s8/p1p1p1kpp/3P2p2/5p3/Pp3PPP1/1P1SP3P/2R6/1N1GKG1NL b BGLr2sp 67",
+        "ln1s3nl/1+S4+B2/p1p1k2pp/3p2P2/7P1/P1Pnpp1R1/1P1P+lP2P/2G1G4/L1SKP2N+b b R2GPsp 73",
+        "lnB2k1+Pl/4g1g2/p1pp2ppp/5r3/3s2P1P/NpPnPP3/P5Sb1/3S5/LNG1KG2L w rs4p 74",
+        // head -10 mate7.sfen
+        "ln1g3+Rl/2sk1s+P2/2ppppb1p/p1b3p2/8P/P4P3/2PPP1P2/1+r2GS3/LN+p2KGNL w GN2Ps 36",
+        "ln1g2B+Rl/2s6/pPppppk2/6p1p/9/4P1P1P/P1PPSP3/3+psK3/L+r3G1NL b SNb2gn2p 39",
+        "ln+P3s+Pl/2+R1Gsk2/p3pp1g1/4r1ppp/1NS6/6P2/PP1+bPPS1P/3+p1K3/LG3G1NL w Nb3p 72",
+        "lnsgk2+Pl/6+N2/p1pp2p1p/4p2R1/9/2P3P2/P2PPPN1P/4s1g1K/L4+r2L w 2B2SN4P2g 56",
+        "l+P1g2+S1l/2sk5/p1ppppngp/6p2/9/6P2/P1+bPPP2P/2+r2S3/+rN2GK1NL w SNbgl4p 56",
+        "l2R2snl/4gkg2/p+P1ppp2p/2p3pp1/9/1nPPP4/P1G1GPP1P/3K1Ss2/+r3Bb1NL w N2Psl 68",
+        "l6nl/3k2+B2/p1n1g2pp/2G1ppp2/2P2N1P1/3P2P1P/Ps1GP4/1+rSK2R2/LN6L b G3Pb2s2p 77",
+        "+N5snl/4+N1gp1/1b1p1pkP1/1s1l2pLp/4p+b3/P1P6/1P1PPPP1P/2+rSK2L1/2+r1S1GN1 w 2P2gp 84",
+        // TODO: "ln3kgRl/2s1g2p1/2ppppn1p/p5p2/6b2/P3P4/1+rPP1PP1P/1P4S2/LNSK1G1NL w GPbsp 50",
+        "3g4l/+R1sg2S2/p1npk1s+Rp/2pb2p2/4g2N1/1p7/P1PP1PP1P/1P1S5/LNK2G1+lL b N3Pb2p 71",
+    ];
+
+    // ghi_problems と differential_ghi で共有する
+    const GHI_PROBLEMS: &[&str] = &[
+        "ln1gkg1nl/6+P2/2sppps1p/2p3p2/p8/P1P1P3P/2NP1PP2/3s1KSR1/L1+b2G1NL w R2Pbgp 42", // https://yaneuraou.yaneu.com/2020/12/25/christmas-present/ mate3.sfen:1
+        "3Bp2n1/5+R2+B/p2p1GSp1/8p/Pn5l1/1n2SNP2/2pPPS1Pk/1P1SK1G2/L1G1G4 b RL3Pl3p 131", // https://yaneuraou.yaneu.com/2020/12/25/christmas-present/ mate7.sfen:71
+        "7+P1/5R1s1/6ks1/9/5L1p1/9/9/9/9 b R2b4g2s4n3l16p 1", // https://www.shogi.or.jp/tsume_shogi/everyday/20211183_1.html
+    ];
+
     #[test]
     fn solve_mates() {
-        // https://yaneuraou.yaneu.com/2020/12/25/christmas-present/
-        let test_cases = vec![
-            // head -10 mate3.sfen
-            "ln1gkg1nl/6+P2/2sppps1p/2p3p2/p8/P1P1P3P/2NP1PP2/3s1KSR1/L1+b2G1NL w R2Pbgp 42",
-            "l3kgsnl/9/p1pS+Bp3/7pp/6PP1/9/PPPPPPn1P/1B1GG2+r1/LNS1K3L w RG3Psnp 54",
-            "l3k2nl/4g1gb1/1+S1pspp+P1/p1p6/3n4p/2PPR1P2/P2bPP2P/5GS2/LN1K4L w R2Pgsn2p 50",
-            "lns+R4l/1p1p5/p1pkppB1p/6p2/1R7/6P1P/P1PPnPS2/2+b1G1g2/L3K1sNL b 2GS3Pnp 51",
-            "1+P1gkg2l/2s3s+P1/3ppp2p/P1p2npp1/l2N1+b3/3KP1P2/N2P1PS1P/2+p1G2R1/L1+r3sNL w Pbgp 58",
-            "lnsG5/4g4/prpp1p1pp/1p4p2/4+B3k/2P1P4/P+b1PSP1LP/4K2SL/2G2G1r1 b SP3nl3p 71",
-            "l5+R1l/4kS3/p4pnpp/2Pppb3/6p1P/P2s5/NP2+nPPR1/2+bS2GK1/L6NL b 3GSP4p 93",
-            "lR5nl/5k1b1/2gp3p1/2s1p1P2/p4N2p/P3PpR2/1PPP1P2P/2G1K2s1/LN6L b GSN2Pbgs2p 83",
-            "l1+R5l/2pS5/p2pp+P1pp/2k3p2/2N4P1/PP2R1P1P/2+pPP1N2/2GSG1bs1/LN1K4L b 2GSNPbp 73",
-            "lnsg4l/1r1b5/p1pp1+N1+R1/4p3p/9/P3SSk2/NpPPPPg1P/2GK5/L1S4NL b 2Pbg4p 91",
-            // head -10 mate5.sfen
-            "l2gkg2l/2s3s2/p1nppp1pp/2p3p2/P4P1P1/4n3P/1PPPG1N2/1BKS2+s2/LN3+r3 w RBgl3p 72",
-            "lnsgs2+Pl/3kg4/p1pppN2p/6pp1/9/7R1/P1PP1Sg1P/1S3+b3/LN5KL w Nrbg6p 58",
-            "lnG4nl/5k3/p1p+R1g1p1/1p1p3sp/5N3/2P1p1p2/PP1GP3P/1SG2+p1+b1/LN1K4L w Srbs4p 60",
-            "ln4knl/4+N2b1/4ppsG1/p1P5p/2G3pp1/3P1P2P/P2+pP1P2/2+srSK3/L+r3G1NL w G4Pbs 78",
-            "ln5+Pl/3s1kg+R1/p2ppl2p/2ps1Bp2/P8/2P3P1P/N2gP4/5KS2/L+r3G1N+b b GS3Pn3p 57",
-            "l3k3l/1r1sg1B2/3p2+R1p/2p1pN2P/9/pPP1PP3/3PK1P2/2G1sg3/LNS2+n1NL b 5Pbgsp 69",
-            "ln1g4l/2s2+R3/2kp2G2/p1r2pp1p/4S4/Pp1Gp1P2/BP1P4P/2GKs1+bP1/LNN5L w SN4Pp 88",
-            "ln1+P1GBnl/s8/p1p1p1kpp/3P2p2/5p3/Pp3PPP1/1P1SP3P/2R6/1N1GKG1NL b BGLr2sp 67",
-            "ln1s3nl/1+S4+B2/p1p1k2pp/3p2P2/7P1/P1Pnpp1R1/1P1P+lP2P/2G1G4/L1SKP2N+b b R2GPsp 73",
-            "lnB2k1+Pl/4g1g2/p1pp2ppp/5r3/3s2P1P/NpPnPP3/P5Sb1/3S5/LNG1KG2L w rs4p 74",
-            // head -10 mate7.sfen
-            "ln1g3+Rl/2sk1s+P2/2ppppb1p/p1b3p2/8P/P4P3/2PPP1P2/1+r2GS3/LN+p2KGNL w GN2Ps 36",
-            "ln1g2B+Rl/2s6/pPppppk2/6p1p/9/4P1P1P/P1PPSP3/3+psK3/L+r3G1NL b SNb2gn2p 39",
-            "ln+P3s+Pl/2+R1Gsk2/p3pp1g1/4r1ppp/1NS6/6P2/PP1+bPPS1P/3+p1K3/LG3G1NL w Nb3p 72",
-            "lnsgk2+Pl/6+N2/p1pp2p1p/4p2R1/9/2P3P2/P2PPPN1P/4s1g1K/L4+r2L w 2B2SN4P2g 56",
-            "l+P1g2+S1l/2sk5/p1ppppngp/6p2/9/6P2/P1+bPPP2P/2+r2S3/+rN2GK1NL w SNbgl4p 56",
-            "l2R2snl/4gkg2/p+P1ppp2p/2p3pp1/9/1nPPP4/P1G1GPP1P/3K1Ss2/+r3Bb1NL w N2Psl 68",
-            "l6nl/3k2+B2/p1n1g2pp/2G1ppp2/2P2N1P1/3P2P1P/Ps1GP4/1+rSK2R2/LN6L b G3Pb2s2p 77",
-            "+N5snl/4+N1gp1/1b1p1pkP1/1s1l2pLp/4p+b3/P1P6/1P1PPPP1P/2+rSK2L1/2+r1S1GN1 w 2P2gp 84",
-            // TODO: "ln3kgRl/2s1g2p1/2ppppn1p/p5p2/6b2/P3P4/1+rPP1PP1P/1P4S2/LNSK1G1NL w GPbsp 50",
-            "3g4l/+R1sg2S2/p1npk1s+Rp/2pb2p2/4g2N1/1p7/P1PP1PP1P/1P1S5/LNK2G1+lL b N3Pb2p 71",
-        ];
-        for (i, &sfen) in test_cases.iter().enumerate() {
+        for (i, &sfen) in MATES.iter().enumerate() {
             let pos =
                 PartialPosition::from_usi(&format!("sfen {sfen}")).expect("failed to parse sfen");
             match solve::<YasaiPosition, HashMapTable>(pos, Some(Duration::from_secs(5))) {
@@ -64,14 +95,50 @@ mod tests {
         }
     }
 
+    fn compare_all(test_cases: &[&str]) {
+        for (i, &sfen) in test_cases.iter().enumerate() {
+            let pos =
+                PartialPosition::from_usi(&format!("sfen {sfen}")).expect("failed to parse sfen");
+            if let Err(e) = compare_positions::<YasaiPosition, ReferencePosition, HashMapTable>(
+                &pos,
+                Some(Duration::from_secs(60)),
+            ) {
+                panic!("mismatch #{i}: {e}");
+            }
+        }
+    }
+
+    #[test]
+    fn differential() {
+        // 通常の実行では速く終わる 3手・5手詰と不詰の局面だけ比べる
+        compare_all(&[
+            MATES[0],
+            MATES[1],
+            MATES[3],
+            MATES[10],
+            "ln1g3k1/5G2l/1+LspSp2p/2p1S2p1/2r3p2/p3P4/1P+BP1P+b1P/2GS5/L2K1G3 b NPr2n5p 79",
+            "7nl/5B1k1/6Ppp/5+R3/9/9/9/9/9 b Srb4g3s3n3l15p 1",
+            "9/9/3pp4/+r2k1p3/2L1+p4/2+R6/B8/B8/9 b 4g4s4n3l14p 1",
+            "lnsgkgsnl/1r5b1/ppppppppp/9/9/9/PPPPPPPPP/1B5R1/LNSGKGSNL b - 1",
+        ]);
+    }
+
+    // ReferencePosition は遅いので、全問の比較は --ignored で実行する
+    #[test]
+    #[ignore]
+    fn differential_mates() {
+        compare_all(MATES);
+    }
+
+    #[test]
+    #[ignore]
+    fn differential_ghi() {
+        compare_all(GHI_PROBLEMS);
+    }
+
     #[test]
     fn ghi_problems() {
-        let test_cases = vec![
-            "ln1gkg1nl/6+P2/2sppps1p/2p3p2/p8/P1P1P3P/2NP1PP2/3s1KSR1/L1+b2G1NL w R2Pbgp 42", // https://yaneuraou.yaneu.com/2020/12/25/christmas-present/ mate3.sfen:1
-            "3Bp2n1/5+R2+B/p2p1GSp1/8p/Pn5l1/1n2SNP2/2pPPS1Pk/1P1SK1G2/L1G1G4 b RL3Pl3p 131", // https://yaneuraou.yaneu.com/2020/12/25/christmas-present/ mate7.sfen:71
-            "7+P1/5R1s1/6ks1/9/5L1p1/9/9/9/9 b R2b4g2s4n3l16p 1", // https://www.shogi.or.jp/tsume_shogi/everyday/20211183_1.html
-        ];
-        for (i, &sfen) in test_cases.iter().enumerate() {
+        for (i, &sfen) in GHI_PROBLEMS.iter().enumerate() {
             let pos =
                 PartialPosition::from_usi(&format!("sfen {sfen}")).expect("failed to parse sfen");
             match solve::<YasaiPosition, HashMapTable>(pos, Some(Duration::from_secs(5))) {
@@ -141,4 +208,268 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn mating_move() {
+        let pos =
+            PartialPosition::from_usi("sfen 9/9/3pp4/+r2k1p3/2L1+p4/2+R6/B8/B8/9 b 4g4s4n3l14p 1")
+                .expect("failed to parse sfen");
+        let test_cases = vec![("7e7b+", true), ("9h8i", false)];
+        for (usi, expected) in test_cases {
+            let m = Move::from_usi(usi).expect("failed to parse move");
+            match is_mating_move::<YasaiPosition, HashMapTable>(
+                &pos,
+                m,
+                Some(Duration::from_secs(5)),
+            ) {
+                Ok(ret) => {
+                    assert_eq!(ret, expected, "failed to check {usi}");
+                }
+                Err(e) => {
+                    panic!("canceled {usi}: {e}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn verify_cases() {
+        let test_cases = vec![
+            (
+                "9/9/3pp4/+r2k1p3/2L1+p4/2+R6/B8/B8/9 b 4g4s4n3l14p 1\t3\t7e7b+ N*8f 7f7c",
+                true,
+            ),
+            (
+                "9/9/3pp4/+r2k1p3/2L1+p4/2+R6/B8/B8/9 b 4g4s4n3l14p 1\t5",
+                false,
+            ),
+            (
+                "9/9/3pp4/+r2k1p3/2L1+p4/2+R6/B8/B8/9 b 4g4s4n3l14p 1\t3\t7f7c",
+                false,
+            ),
+            (
+                "lnsgkgsnl/1r5b1/ppppppppp/9/9/9/PPPPPPPPP/1B5R1/LNSGKGSNL b - 1\t0",
+                true,
+            ),
+        ];
+        for (i, (line, expected)) in test_cases.into_iter().enumerate() {
+            let case = line
+                .parse::<RegressionCase>()
+                .expect("failed to parse case");
+            let ret =
+                verify_case::<YasaiPosition, HashMapTable>(&case, Some(Duration::from_secs(5)));
+            assert_eq!(ret.is_ok(), expected, "failed to verify #{i}: {ret:?}");
+        }
+    }
+
+    #[test]
+    fn verify_solutions() {
+        let pos =
+            PartialPosition::from_usi("sfen 9/9/3pp4/+r2k1p3/2L1+p4/2+R6/B8/B8/9 b 4g4s4n3l14p 1")
+                .expect("failed to parse sfen");
+        let test_cases = vec![
+            ("7e7b+ N*8f 7f7c", None),
+            ("7e7b+ N*8f", Some((2, FlawKind::EndsWithDefence))),
+            ("9h8i N*8f 7f7c", Some((1, FlawKind::NotCheck))),
+            ("7e7b+ 1a1b", Some((2, FlawKind::Illegal))),
+            ("", Some((0, FlawKind::NoMoves))),
+        ];
+        for (usi, expected) in test_cases {
+            let moves = usi
+                .split_whitespace()
+                .map(|s| Move::from_usi(s).expect("failed to parse move"))
+                .collect::<Vec<_>>();
+            match verify_solution::<YasaiPosition, HashMapTable>(
+                &pos,
+                &moves,
+                Some(Duration::from_secs(5)),
+            ) {
+                Ok(ret) => {
+                    assert_eq!(
+                        ret.map(|flaw| (flaw.ply, flaw.kind)),
+                        expected,
+                        "failed to verify {usi}"
+                    );
+                }
+                Err(e) => {
+                    panic!("canceled {usi}: {e}");
+                }
+            }
+        }
+
+        // G*5b で詰むが、主張された G*4b は取られて逃れる。余詰ではなく逃れとして報告する
+        let pos = PartialPosition::from_usi("sfen 4k4/9/4P4/9/9/9/9/9/9 b G 1")
+            .expect("failed to parse sfen");
+        let moves = ["G*4b", "5a4b", "5c5b+"]
+            .iter()
+            .map(|s| Move::from_usi(s).expect("failed to parse move"))
+            .collect::<Vec<_>>();
+        let flaw = verify_solution::<YasaiPosition, HashMapTable>(
+            &pos,
+            &moves,
+            Some(Duration::from_secs(5)),
+        )
+        .expect("canceled")
+        .expect("no flaw");
+        assert_eq!((flaw.ply, flaw.kind), (1, FlawKind::Escape));
+    }
+
+    #[test]
+    fn refutations() {
+        // 攻方は持駒無し、王手は 5c5b と 5c5b+ の 2 通り
+        let pos = PartialPosition::from_usi("sfen 4k4/9/4P4/9/9/9/9/9/4K4 b 2r2b4g4s4n4l17p 1")
+            .expect("failed to parse sfen");
+        let mut searcher: CancelableSearcher<YasaiPosition, HashMapTable> =
+            CancelableSearcher::new(YasaiPosition::from(pos), Some(Duration::from_secs(5)));
+        searcher.dfpn_search().expect("canceled");
+        assert!(best_solution(&mut searcher).is_empty());
+        let refutations = refute(&mut searcher, 2);
+        assert_eq!(refutations.len(), 2);
+        for refutation in refutations {
+            assert!(refutation.escape.is_some(), "{refutation:?}");
+        }
+    }
+
+    #[test]
+    fn hints() {
+        let pos =
+            PartialPosition::from_usi("sfen 9/9/3pp4/+r2k1p3/2L1+p4/2+R6/B8/B8/9 b 4g4s4n3l14p 1")
+                .expect("failed to parse sfen");
+        let mut hinter: Hinter<YasaiPosition, HashMapTable> =
+            Hinter::new(pos, Some(Duration::from_secs(5)));
+        let hint = hinter.hint(1).expect("canceled");
+        assert_eq!(hint.moves, vec![Move::from_usi("7e7b+").unwrap()]);
+        assert_eq!(hint.remaining, 2);
+        // 2 回目以降は探索しない
+        let nodes = hinter.nodes();
+        let hint = hinter.hint(5).expect("canceled");
+        assert_eq!((hint.moves.len(), hint.remaining), (3, 0));
+        assert_eq!(hinter.nodes(), nodes);
+    }
+
+    #[test]
+    fn cancel_keeps_intermediate_numbers() {
+        let pos =
+            PartialPosition::from_usi("sfen 9/9/3pp4/+r2k1p3/2L1+p4/2+R6/B8/B8/9 b 4g4s4n3l14p 1")
+                .expect("failed to parse sfen");
+        let mut searcher: CancelableSearcher<YasaiPosition, HashMapTable> =
+            CancelableSearcher::new(YasaiPosition::from(pos), None);
+        searcher.set_node_limit(Some(5));
+        assert!(matches!(
+            searcher.dfpn_search(),
+            Err(CanceledError::NodeLimit)
+        ));
+        // ルートにはサイクル回避用のしきい値ではなく、中断時点の証明数・反証数が残る
+        let root = dfpn::Position::hash_key(&searcher.pos);
+        let (pn, dn) = dfpn::Table::look_up_hash(searcher.table(), &root);
+        assert!(0 < pn && pn < u32::MAX - 1, "pn = {pn}");
+        assert!(0 < dn && dn < u32::MAX - 1, "dn = {dn}");
+    }
+
+    #[test]
+    fn resume() {
+        let pos =
+            PartialPosition::from_usi("sfen 9/9/3pp4/+r2k1p3/2L1+p4/2+R6/B8/B8/9 b 4g4s4n3l14p 1")
+                .expect("failed to parse sfen");
+        let mut resumable: Resumable<YasaiPosition, HashMapTable> = Resumable::new(pos);
+        assert!(matches!(
+            resumable.search(Some(Duration::ZERO)),
+            Err(CanceledError::Timeout)
+        ));
+        assert!(!resumable.is_finished());
+        // 置換表を引き継いで続きから探索する
+        let moves = resumable
+            .search(Some(Duration::from_secs(5)))
+            .expect("canceled")
+            .to_vec();
+        assert_eq!(
+            moves,
+            ["7e7b+", "N*8f", "7f7c"].map(|usi| Move::from_usi(usi).unwrap())
+        );
+        // 解けた後は探索しない
+        let nodes = resumable.nodes();
+        assert_eq!(
+            resumable.search(Some(Duration::ZERO)).ok(),
+            Some(moves.as_slice())
+        );
+        assert_eq!(resumable.nodes(), nodes);
+    }
+
+    #[test]
+    fn table_file() {
+        let pos =
+            PartialPosition::from_usi("sfen 9/9/3pp4/+r2k1p3/2L1+p4/2+R6/B8/B8/9 b 4g4s4n3l14p 1")
+                .expect("failed to parse sfen");
+        let mut searcher: CancelableSearcher<YasaiPosition, HashMapTable> = CancelableSearcher::new(
+            YasaiPosition::from(pos.clone()),
+            Some(Duration::from_secs(5)),
+        );
+        searcher.dfpn_search().expect("canceled");
+        let expected = best_solution(&mut searcher);
+        let root = dfpn::Position::hash_key(&searcher.pos);
+        let mut buf = Vec::new();
+        write_table(searcher.table(), root, &mut buf).expect("failed to write");
+        assert_eq!(buf.len(), 22 + searcher.table().len() * 16);
+
+        // 読み込んだ置換表でルートは証明済みなので探索しない
+        let table = read_table(buf.as_slice(), root).expect("failed to read");
+        let mut searcher: CancelableSearcher<YasaiPosition, HashMapTable> =
+            CancelableSearcher::new(YasaiPosition::from(pos), Some(Duration::from_secs(5)));
+        searcher.set_table(table);
+        searcher.dfpn_search().expect("canceled");
+        assert_eq!(searcher.nodes(), 0);
+        assert_eq!(best_solution(&mut searcher), expected);
+
+        assert!(matches!(
+            read_table::<u32, _>(buf.as_slice(), root ^ 1),
+            Err(TableFileError::Position)
+        ));
+        assert!(matches!(
+            read_table::<u64, _>(buf.as_slice(), root),
+            Err(TableFileError::Width {
+                expected: 8,
+                actual: 4
+            })
+        ));
+        assert!(matches!(
+            read_table::<u32, _>(&buf[..buf.len() - 1], root),
+            Err(TableFileError::Corrupted)
+        ));
+    }
+
+    #[test]
+    fn proof_trees() {
+        let pos =
+            PartialPosition::from_usi("sfen 9/9/3pp4/+r2k1p3/2L1+p4/2+R6/B8/B8/9 b 4g4s4n3l14p 1")
+                .expect("failed to parse sfen");
+        let mut searcher: CancelableSearcher<YasaiPosition, HashMapTable> =
+            CancelableSearcher::new(YasaiPosition::from(pos), Some(Duration::from_secs(5)));
+        searcher.dfpn_search().expect("canceled");
+        let tree = proof_tree(&mut searcher, TreeLimits::default());
+        assert_eq!((tree.m, tree.pn, tree.dn), (None, 0, u64::MAX));
+        assert_eq!(tree.length, Some(3));
+        assert!(!tree.truncated);
+        let first = Move::from_usi("7e7b+").unwrap();
+        assert!(tree.children.iter().any(|child| child.m == Some(first)));
+
+        // 上限で打ち切った部分木は手数が分からない
+        let tree = proof_tree(
+            &mut searcher,
+            TreeLimits {
+                depth: 1,
+                nodes: 1000,
+            },
+        );
+        assert!(tree.length.is_none());
+        assert!(tree.children.iter().all(|child| child.truncated));
+        let tree = proof_tree(
+            &mut searcher,
+            TreeLimits {
+                depth: 15,
+                nodes: 2,
+            },
+        );
+        assert_eq!(tree.children.len(), 1);
+        assert!(tree.children[0].children.is_empty());
+    }
 }
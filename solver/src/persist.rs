@@ -0,0 +1,128 @@
+use crate::dfpn_extended::CancelableSearcher;
+use crate::implementations::HashMapTable;
+use dfpn::Position;
+use num_traits::{PrimInt, SaturatingAdd, Unsigned};
+use std::fs::{self, File};
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::mem::size_of;
+use std::path::Path;
+use thiserror::Error;
+
+const MAGIC: &[u8; 4] = b"TSTB";
+const VERSION: u8 = 1;
+
+#[derive(Error, Debug)]
+pub enum TableFileError {
+    #[error(transparent)]
+    Io(#[from] io::Error),
+    #[error("not a table file")]
+    Magic,
+    #[error("unsupported table file version: {0}")]
+    Version(u8),
+    #[error("table file holds {actual}-byte numbers, expected {expected}")]
+    Width { expected: usize, actual: usize },
+    #[error("table file was saved for a different position")]
+    Position,
+    #[error("corrupted table file")]
+    Corrupted,
+}
+
+// リトルエンディアンで
+// `"TSTB" | 版 (u8) | 数値の幅 (u8) | ルート局面のハッシュ (u64) | 件数 (u64)` の後に
+// `ハッシュ (u64) | 証明数・反証数 (幅バイトずつ)` を件数分並べる
+pub fn write_table<U, W>(
+    table: &HashMapTable<U>,
+    root: u64,
+    writer: W,
+) -> Result<(), TableFileError>
+where
+    U: PrimInt + Unsigned,
+    W: Write,
+{
+    let width = size_of::<U>();
+    let mut writer = BufWriter::new(writer);
+    writer.write_all(MAGIC)?;
+    writer.write_all(&[VERSION, width as u8])?;
+    writer.write_all(&root.to_le_bytes())?;
+    writer.write_all(&(table.len() as u64).to_le_bytes())?;
+    for (key, (pn, dn)) in table.iter() {
+        writer.write_all(&key.to_le_bytes())?;
+        for n in [pn, dn] {
+            let n = n.to_u64().ok_or(TableFileError::Corrupted)?;
+            writer.write_all(&n.to_le_bytes()[..width])?;
+        }
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+// root が保存時のルート局面と違えば読み込まない
+pub fn read_table<U, R>(reader: R, root: u64) -> Result<HashMapTable<U>, TableFileError>
+where
+    U: PrimInt + Unsigned,
+    R: Read,
+{
+    let width = size_of::<U>();
+    let mut reader = BufReader::new(reader);
+    let mut magic = [0; 4];
+    reader.read_exact(&mut magic)?;
+    if &magic != MAGIC {
+        return Err(TableFileError::Magic);
+    }
+    let mut header = [0; 2];
+    reader.read_exact(&mut header)?;
+    match header {
+        [VERSION, w] if w as usize == width => {}
+        [VERSION, w] => {
+            return Err(TableFileError::Width {
+                expected: width,
+                actual: w as usize,
+            })
+        }
+        [version, _] => return Err(TableFileError::Version(version)),
+    }
+    if read_u64(&mut reader, 8)? != root {
+        return Err(TableFileError::Position);
+    }
+    let len = read_u64(&mut reader, 8)?;
+    (0..len)
+        .map(|_| {
+            let key = read_u64(&mut reader, 8)?;
+            let pn = U::from(read_u64(&mut reader, width)?).ok_or(TableFileError::Corrupted)?;
+            let dn = U::from(read_u64(&mut reader, width)?).ok_or(TableFileError::Corrupted)?;
+            Ok((key, (pn, dn)))
+        })
+        .collect()
+}
+
+fn read_u64<R: Read>(reader: &mut R, width: usize) -> Result<u64, TableFileError> {
+    let mut buf = [0; 8];
+    reader
+        .read_exact(&mut buf[..width])
+        .map_err(|e| match e.kind() {
+            io::ErrorKind::UnexpectedEof => TableFileError::Corrupted,
+            _ => e.into(),
+        })?;
+    Ok(u64::from_le_bytes(buf))
+}
+
+impl<P, U> CancelableSearcher<P, HashMapTable<U>>
+where
+    P: Position,
+    U: PrimInt + Unsigned + SaturatingAdd + Default,
+{
+    // 書き込み途中で落ちても前回のファイルが残るよう、一時ファイルに書いてから置き換える
+    pub fn save_table<Q: AsRef<Path>>(&self, path: Q) -> Result<(), TableFileError> {
+        let path = path.as_ref();
+        let mut tmp = path.as_os_str().to_owned();
+        tmp.push(".tmp");
+        write_table(self.table(), self.pos.hash_key(), File::create(&tmp)?)?;
+        fs::rename(&tmp, path)?;
+        Ok(())
+    }
+    pub fn load_table<Q: AsRef<Path>>(&mut self, path: Q) -> Result<(), TableFileError> {
+        let table = read_table(File::open(path)?, self.pos.hash_key())?;
+        self.set_table(table);
+        Ok(())
+    }
+}
@@ -0,0 +1,142 @@
+use crate::dfpn_extended::CancelableSearcher;
+use dfpn::search::Search;
+use dfpn::{Node, Position, Table};
+use num_traits::{Bounded, ToPrimitive, Zero};
+use shogi_core::Move;
+use std::collections::HashSet;
+
+// 証明木の 1 ノード。pn, dn の ∞ は u64::MAX で表す
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProofNode {
+    // このノードに至る手 (ルートは None)
+    pub m: Option<Move>,
+    pub node: Node,
+    pub pn: u64,
+    pub dn: u64,
+    // このノードから詰みまでの手数 (無駄合は除かない)。上限で打ち切った部分木では None
+    pub length: Option<usize>,
+    // 深さかノード数の上限で子ノードを省いたか、AND ノードで証明済みでない
+    // (または手順中に現れる局面に戻る) 応手を省いた
+    pub truncated: bool,
+    pub children: Vec<ProofNode>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TreeLimits {
+    pub depth: usize,
+    pub nodes: usize,
+}
+
+impl Default for TreeLimits {
+    fn default() -> Self {
+        Self {
+            depth: 15,
+            nodes: 1000,
+        }
+    }
+}
+
+// 詰みが証明された置換表から、search_all_mates が辿るのと同じ AND/OR 木を取り出す
+pub fn proof_tree<P, T>(searcher: &mut CancelableSearcher<P, T>, limits: TreeLimits) -> ProofNode
+where
+    P: Position,
+    T: Table,
+{
+    let hash = searcher.hash_key();
+    let value = searcher.look_up_hash(&hash);
+    let mut builder = Builder {
+        searcher,
+        limits,
+        count: 1,
+        hashes: HashSet::from([hash]),
+    };
+    builder.build(None, Node::Or, value, 0)
+}
+
+struct Builder<'a, P, T> {
+    searcher: &'a mut CancelableSearcher<P, T>,
+    limits: TreeLimits,
+    count: usize,
+    hashes: HashSet<u64>,
+}
+
+impl<P, T> Builder<'_, P, T>
+where
+    P: Position,
+    T: Table,
+{
+    // value は置換表の値そのもの (AND ノードでは (dn, pn) の順)
+    fn build(
+        &mut self,
+        m: Option<Move>,
+        node: Node,
+        value: (T::U, T::U),
+        depth: usize,
+    ) -> ProofNode {
+        let (pn, dn) = match node {
+            Node::Or => value,
+            Node::And => (value.1, value.0),
+        };
+        let mut ret = ProofNode {
+            m,
+            node,
+            pn: to_u64::<T>(pn),
+            dn: to_u64::<T>(dn),
+            length: None,
+            truncated: false,
+            children: Vec::new(),
+        };
+        if pn != T::U::zero() {
+            return ret;
+        }
+        let mate_pd = match node {
+            Node::Or => (T::U::max_value(), T::U::zero()),
+            Node::And => (T::U::zero(), T::U::max_value()),
+        };
+        let moves = self.searcher.generate_legal_moves(node);
+        // 絞り込む前の合法手で、応手が無い (詰んでいる) かを判定する
+        if node == Node::And && moves.is_empty() {
+            ret.length = Some(0);
+            return ret;
+        }
+        let total = moves.len();
+        let children = moves
+            .into_iter()
+            .filter(|(_, h)| !self.hashes.contains(h) && self.searcher.look_up_hash(h) == mate_pd)
+            .collect::<Vec<_>>();
+        if node == Node::And && children.len() < total {
+            ret.truncated = true;
+        }
+        for (m, h) in children {
+            if depth >= self.limits.depth || self.count >= self.limits.nodes {
+                ret.truncated = true;
+                break;
+            }
+            self.count += 1;
+            self.hashes.insert(h);
+            self.searcher.do_move(m);
+            let child = self.build(Some(m.into()), !node, mate_pd, depth + 1);
+            self.searcher.undo_move(m);
+            self.hashes.remove(&h);
+            ret.children.push(child);
+        }
+        if !ret.truncated && ret.children.iter().all(|child| child.length.is_some()) {
+            let lengths = ret.children.iter().filter_map(|child| child.length);
+            // 攻方は最短、玉方は最長の手順を選ぶ
+            ret.length = match node {
+                Node::Or => lengths.min(),
+                Node::And => lengths.max(),
+            }
+            .map(|len| len + 1);
+        }
+        ret
+    }
+}
+
+fn to_u64<T: Table>(n: T::U) -> u64 {
+    if n == T::U::max_value() {
+        u64::MAX
+    } else {
+        n.to_u64().unwrap_or(u64::MAX)
+    }
+}
@@ -0,0 +1,46 @@
+use crate::dfpn_extended::CancelableSearcher;
+use dfpn::search::Search;
+use dfpn::{Node, Position, Table};
+use num_traits::{Bounded, Zero};
+use shogi_core::Move;
+
+// 攻方の王手ごとに、詰みを逃れる玉方の応手とその先の王手への反証
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Refutation {
+    pub check: Move,
+    pub escape: Option<Move>,
+    pub children: Vec<Refutation>,
+}
+
+// 不詰が証明された置換表から、depth 手目までの攻方の王手に対する反証を取り出す
+pub fn refute<P, T>(searcher: &mut CancelableSearcher<P, T>, depth: usize) -> Vec<Refutation>
+where
+    P: Position,
+    T: Table,
+{
+    let mut ret = Vec::new();
+    if depth == 0 {
+        return ret;
+    }
+    for (c, _) in searcher.generate_legal_moves(Node::Or) {
+        searcher.do_move(c);
+        // 玉方から見て不詰の局面は (pn, dn) = (∞, 0) として記録されている
+        let escape = searcher
+            .generate_legal_moves(Node::And)
+            .into_iter()
+            .find(|(_, h)| searcher.look_up_hash(h) == (T::U::max_value(), T::U::zero()));
+        let mut refutation = Refutation {
+            check: c.into(),
+            escape: escape.map(|(r, _)| r.into()),
+            children: Vec::new(),
+        };
+        if let Some((r, _)) = escape {
+            searcher.do_move(r);
+            refutation.children = refute(searcher, depth - 1);
+            searcher.undo_move(r);
+        }
+        searcher.undo_move(c);
+        ret.push(refutation);
+    }
+    ret
+}
@@ -0,0 +1,122 @@
+use crate::dfpn_extended::CanceledError;
+use crate::solve::{solve, CalculateResult};
+use dfpn::{Position, Table};
+use shogi_core::{Move, PartialPosition, ToUsi};
+use shogi_usi_parser::FromUsi;
+use std::str::FromStr;
+use std::time::Duration;
+use thiserror::Error;
+
+// `sfen<TAB>手数<TAB>USI の手順` の 1 行。手数 0 は不詰、手順は省略可
+pub struct RegressionCase {
+    pub position: PartialPosition,
+    pub length: usize,
+    pub moves: Option<Vec<Move>>,
+}
+
+#[derive(Error, Debug)]
+pub enum RegressionCaseError {
+    #[error("invalid sfen: {0}")]
+    Sfen(String),
+    #[error("invalid expected length: {0}")]
+    Length(String),
+    #[error("invalid move: {0}")]
+    Move(String),
+}
+
+#[derive(Error, Debug)]
+pub enum Mismatch {
+    #[error("expected mate in {0}, but no mate was found")]
+    NoMate(usize),
+    #[error("expected no mate, but found mate in {0}")]
+    UnexpectedMate(usize),
+    #[error("expected mate in {expected}, but got mate in {actual}")]
+    Length { expected: usize, actual: usize },
+    #[error("expected {expected}, but got {actual}")]
+    Moves { expected: String, actual: String },
+    #[error(transparent)]
+    Canceled(#[from] CanceledError),
+}
+
+impl FromStr for RegressionCase {
+    type Err = RegressionCaseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut fields = s.trim_end_matches(['\r', '\n']).split('\t');
+        let sfen = fields.next().unwrap_or_default().trim();
+        let position = PartialPosition::from_usi(&format!("sfen {sfen}"))
+            .map_err(|_| RegressionCaseError::Sfen(sfen.to_string()))?;
+        let length = fields.next().unwrap_or_default().trim();
+        let length = length
+            .parse()
+            .map_err(|_| RegressionCaseError::Length(length.to_string()))?;
+        let moves = match fields.next().map(str::trim) {
+            Some(s) if !s.is_empty() => Some(
+                s.split_whitespace()
+                    .map(|usi| {
+                        Move::from_usi(usi).map_err(|_| RegressionCaseError::Move(usi.to_string()))
+                    })
+                    .collect::<Result<_, _>>()?,
+            ),
+            _ => None,
+        };
+        Ok(Self {
+            position,
+            length,
+            moves,
+        })
+    }
+}
+
+// 解いた結果が期待する答えと一致しなければ、その食い違いを返す
+pub fn verify_case<P, T>(case: &RegressionCase, timeout: Option<Duration>) -> Result<(), Mismatch>
+where
+    P: Position + From<PartialPosition> + CalculateResult,
+    T: Table,
+{
+    let actual = solve::<P, T>(case.position.clone(), timeout)?;
+    match (case.length, actual.len()) {
+        (0, 0) => return Ok(()),
+        (0, len) => return Err(Mismatch::UnexpectedMate(len)),
+        (expected, 0) => return Err(Mismatch::NoMate(expected)),
+        (expected, actual) if expected != actual => {
+            return Err(Mismatch::Length { expected, actual })
+        }
+        _ => {}
+    }
+    match &case.moves {
+        Some(expected) if expected != &actual => Err(Mismatch::Moves {
+            expected: usi_moves(expected),
+            actual: usi_moves(&actual),
+        }),
+        _ => Ok(()),
+    }
+}
+
+fn usi_moves(moves: &[Move]) -> String {
+    moves
+        .iter()
+        .map(|m| m.to_usi_owned())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+// 2 つの Position 実装で同じ局面を解き、詰み・不詰と手数が食い違えば返す。
+// 同じ手数の詰みが複数あれば選ばれる手順は生成順で変わるので、手順までは比べない
+pub fn compare_positions<P, Q, T>(
+    position: &PartialPosition,
+    timeout: Option<Duration>,
+) -> Result<(), Mismatch>
+where
+    P: Position + From<PartialPosition> + CalculateResult,
+    Q: Position + From<PartialPosition> + CalculateResult,
+    T: Table,
+{
+    let expected = solve::<P, T>(position.clone(), timeout)?.len();
+    let case = RegressionCase {
+        position: position.clone(),
+        length: expected,
+        moves: None,
+    };
+    verify_case::<Q, T>(&case, timeout)
+}
@@ -0,0 +1,52 @@
+use crate::dfpn_extended::{CancelableSearcher, CanceledError};
+use crate::solve::{best_solution, CalculateResult};
+use dfpn::{Position, Table};
+use instant::Instant;
+use shogi_core::{Move, PartialPosition};
+use std::time::Duration;
+
+// 打ち切られても置換表を捨てず、時間を足して続きから探索できる
+pub struct Resumable<P, T> {
+    searcher: CancelableSearcher<P, T>,
+    solution: Option<Vec<Move>>,
+    elapsed: Duration,
+}
+
+impl<P, T> Resumable<P, T>
+where
+    P: Position + From<PartialPosition> + CalculateResult,
+    T: Table,
+{
+    pub fn new(position: PartialPosition) -> Self {
+        Self {
+            searcher: CancelableSearcher::new(P::from(position), None),
+            solution: None,
+            elapsed: Duration::ZERO,
+        }
+    }
+    // 今回の呼び出しで more_time だけ探索する。解けていれば探索せずに同じ手順を返す
+    pub fn search(&mut self, more_time: Option<Duration>) -> Result<&[Move], CanceledError> {
+        if self.solution.is_none() {
+            let started = Instant::now();
+            self.searcher.set_timeout(more_time);
+            let result = self.searcher.dfpn_search();
+            self.elapsed += started.elapsed();
+            result?;
+            self.solution = Some(best_solution(&mut self.searcher));
+        }
+        Ok(self.solution.as_deref().unwrap_or_default())
+    }
+    pub fn is_finished(&self) -> bool {
+        self.solution.is_some()
+    }
+    // これまでの呼び出しの合計
+    pub fn nodes(&self) -> u64 {
+        self.searcher.nodes()
+    }
+    pub fn elapsed(&self) -> Duration {
+        self.elapsed
+    }
+    pub fn searcher(&mut self) -> &mut CancelableSearcher<P, T> {
+        &mut self.searcher
+    }
+}
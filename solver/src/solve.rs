@@ -10,6 +10,13 @@ pub trait CalculateResult {
     fn calculate_result_and_score(&self, moves: &[Move]) -> (Vec<Move>, usize);
 }
 
+// 詰将棋のルールのうち、解の選び方に関わるもの
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Rules {
+    // 無駄合を有効な応手として手数に数える
+    pub count_futile_interpositions: bool,
+}
+
 pub fn solve<P, T>(
     position: PartialPosition,
     timeout: Option<Duration>,
@@ -20,24 +27,44 @@ where
 {
     let pos = P::from(position);
     let mut searcher: CancelableSearcher<P, T> = CancelableSearcher::new(pos, timeout);
-    searcher.dfpn_search().map(|_| {
-        let mut solutions = Vec::new();
-        search_all_mates(
-            &mut searcher,
-            &mut Vec::new(),
-            &mut HashSet::new(),
-            &mut solutions,
-        );
-        solutions.sort_by_cached_key(|&(_, score)| score);
-        solutions.dedup();
-        solutions
-            .last()
-            .map_or(Vec::new(), |(moves, _)| moves.clone())
-    })
+    searcher.dfpn_search().map(|_| best_solution(&mut searcher))
+}
+
+// 探索済みの置換表から最善の手順を取り出す
+pub fn best_solution<P, T>(searcher: &mut CancelableSearcher<P, T>) -> Vec<Move>
+where
+    P: Position + CalculateResult,
+    T: Table,
+{
+    best_solution_with_rules(searcher, Rules::default())
+}
+
+pub fn best_solution_with_rules<P, T>(
+    searcher: &mut CancelableSearcher<P, T>,
+    rules: Rules,
+) -> Vec<Move>
+where
+    P: Position + CalculateResult,
+    T: Table,
+{
+    let mut solutions = Vec::new();
+    search_all_mates(
+        searcher,
+        rules,
+        &mut Vec::new(),
+        &mut HashSet::new(),
+        &mut solutions,
+    );
+    solutions.sort_by_cached_key(|&(_, score)| score);
+    solutions.dedup();
+    solutions
+        .last()
+        .map_or(Vec::new(), |(moves, _)| moves.clone())
 }
 
 fn search_all_mates<P, T>(
     searcher: &mut CancelableSearcher<P, T>,
+    rules: Rules,
     moves: &mut Vec<Move>,
     hashes: &mut HashSet<u64>,
     solutions: &mut Vec<(Vec<Move>, usize)>,
@@ -56,13 +83,17 @@ fn search_all_mates<P, T>(
         .filter(|(_, h)| !hashes.contains(h) && searcher.look_up_hash(h) == mate_pd)
         .collect::<Vec<_>>();
     if node == Node::And && mate_moves.is_empty() {
-        solutions.push(searcher.pos.calculate_result_and_score(moves));
+        if rules.count_futile_interpositions {
+            solutions.push((moves.clone(), moves.len()));
+        } else {
+            solutions.push(searcher.pos.calculate_result_and_score(moves));
+        }
     } else {
         for &(m, h) in &mate_moves {
             moves.push(m.into());
             hashes.insert(h);
             searcher.do_move(m);
-            search_all_mates(searcher, moves, hashes, solutions);
+            search_all_mates(searcher, rules, moves, hashes, solutions);
             searcher.undo_move(m);
             moves.pop();
             hashes.remove(&h);
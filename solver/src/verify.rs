@@ -0,0 +1,208 @@
+use crate::dfpn_extended::CanceledError;
+use crate::solve::{solve, CalculateResult};
+use dfpn::{Node, Position, Table};
+use shogi_core::{Move, PartialPosition};
+use std::time::Duration;
+use thiserror::Error;
+
+// 王手であり、かつ玉方のどの応手に対しても詰みが続くかどうか
+pub fn is_mating_move<P, T>(
+    position: &PartialPosition,
+    m: Move,
+    timeout: Option<Duration>,
+) -> Result<bool, CanceledError>
+where
+    P: Position + From<PartialPosition> + CalculateResult,
+    T: Table,
+{
+    let is_check = P::from(position.clone())
+        .generate_legal_moves(Node::Or)
+        .into_iter()
+        .any(|(c, _)| Into::<Move>::into(c) == m);
+    if !is_check {
+        return Ok(false);
+    }
+    let mut position = position.clone();
+    position.make_move(m);
+    for (r, _) in P::from(position.clone()).generate_legal_moves(Node::And) {
+        let mut next = position.clone();
+        next.make_move(r.into());
+        if solve::<P, T>(next, timeout)?.is_empty() {
+            return Ok(false);
+        }
+    }
+    Ok(true)
+}
+
+pub enum Defence {
+    // 詰まない応手
+    Escape(Move),
+    // 最長の応手とそれ以降の詰み手順 (既に詰んでいれば空)
+    Mated(Vec<Move>),
+}
+
+// 王手に対する玉方の最善の応手を探す。無駄合 (is_futile) は他に応手が無い場合のみ選ぶ
+pub fn defend<P, T>(
+    position: &PartialPosition,
+    timeout: Option<Duration>,
+) -> Result<Defence, CanceledError>
+where
+    P: Position + From<PartialPosition> + CalculateResult,
+    T: Table,
+{
+    let mut best: Option<(bool, Vec<Move>)> = None;
+    for (r, _) in P::from(position.clone()).generate_legal_moves(Node::And) {
+        let r = r.into();
+        let mut next = position.clone();
+        next.make_move(r);
+        let mut line = solve::<P, T>(next, timeout)?;
+        if line.is_empty() {
+            return Ok(Defence::Escape(r));
+        }
+        let futile = is_futile::<P, T>(position, r, &line, timeout)?;
+        line.insert(0, r);
+        if !matches!(&best, Some((f, v)) if (!f, v.len()) >= (!futile, line.len())) {
+            best = Some((futile, line));
+        }
+    }
+    Ok(Defence::Mated(best.map(|(_, v)| v).unwrap_or_default()))
+}
+
+// 合駒 r をすぐに取られ、取った駒を攻方の持駒から除いても同じ手数以内で詰むなら、
+// その合駒は詰み手数を変えない無駄合とみなす
+fn is_futile<P, T>(
+    position: &PartialPosition,
+    r: Move,
+    line: &[Move],
+    timeout: Option<Duration>,
+) -> Result<bool, CanceledError>
+where
+    P: Position + From<PartialPosition> + CalculateResult,
+    T: Table,
+{
+    let (Move::Drop { to, piece }, Some(&capture)) = (r, line.first()) else {
+        return Ok(false);
+    };
+    if !matches!(capture, Move::Normal { to: c, .. } if c == to) {
+        return Ok(false);
+    }
+    let mut next = position.clone();
+    next.make_move(r);
+    next.make_move(capture);
+    let attacker = next.side_to_move().flip();
+    let hand = next.hand_of_a_player_mut(attacker);
+    match hand.removed(piece.piece_kind()) {
+        Some(removed) => *hand = removed,
+        None => return Ok(false),
+    }
+    let without = solve::<P, T>(next, timeout)?;
+    Ok(!without.is_empty() && without.len() < line.len())
+}
+
+#[derive(Error, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FlawKind {
+    #[error("not a legal check")]
+    NotCheck,
+    #[error("illegal move")]
+    Illegal,
+    #[error("the defender escapes")]
+    Escape,
+    #[error("not mate at the end")]
+    NotMate,
+    #[error("ends with a defender move")]
+    EndsWithDefence,
+    #[error("a longer defence exists")]
+    LongerDefence,
+    #[error("another attacking move also mates (余詰)")]
+    Alternative,
+    #[error("no moves given")]
+    NoMoves,
+}
+
+// 主張された手順が最初に破綻する手 (1 始まり、手順が空なら 0) と、その反証手順
+#[derive(Debug)]
+pub struct Flaw {
+    pub ply: usize,
+    pub kind: FlawKind,
+    pub refutation: Vec<Move>,
+}
+
+// 攻方は毎手王手、玉方は合法手で最長の抵抗、最終局面が詰み、途中に余詰が無いことを確かめる
+pub fn verify_solution<P, T>(
+    position: &PartialPosition,
+    moves: &[Move],
+    timeout: Option<Duration>,
+) -> Result<Option<Flaw>, CanceledError>
+where
+    P: Position + From<PartialPosition> + CalculateResult,
+    T: Table,
+{
+    let flaw = |ply, kind, refutation| {
+        Ok(Some(Flaw {
+            ply,
+            kind,
+            refutation,
+        }))
+    };
+    if moves.is_empty() {
+        return flaw(0, FlawKind::NoMoves, Vec::new());
+    }
+    let mut pos = position.clone();
+    let mut defence = Vec::new();
+    for (i, &m) in moves.iter().enumerate() {
+        if i % 2 == 0 {
+            let checks = P::from(pos.clone())
+                .generate_legal_moves(Node::Or)
+                .into_iter()
+                .map(|(c, _)| Into::<Move>::into(c))
+                .collect::<Vec<_>>();
+            if !checks.contains(&m) {
+                return flaw(i + 1, FlawKind::NotCheck, Vec::new());
+            }
+            // 余詰より先に、主張された手自体が詰むかを確かめる
+            let mut next = pos.clone();
+            next.make_move(m);
+            match defend::<P, T>(&next, timeout)? {
+                Defence::Escape(r) => return flaw(i + 1, FlawKind::Escape, vec![r]),
+                Defence::Mated(v) => defence = v,
+            }
+            // 最終手の別解は余詰として扱わない
+            if i + 1 < moves.len() {
+                for &c in checks.iter().filter(|&&c| c != m) {
+                    if is_mating_move::<P, T>(&pos, c, timeout)? {
+                        let mut next = pos.clone();
+                        next.make_move(c);
+                        let mut refutation = vec![c];
+                        if let Defence::Mated(v) = defend::<P, T>(&next, timeout)? {
+                            refutation.extend(v);
+                        }
+                        return flaw(i + 1, FlawKind::Alternative, refutation);
+                    }
+                }
+            }
+            pos = next;
+        } else {
+            let legal = P::from(pos.clone())
+                .generate_legal_moves(Node::And)
+                .into_iter()
+                .any(|(r, _)| Into::<Move>::into(r) == m);
+            if !legal {
+                return flaw(i + 1, FlawKind::Illegal, Vec::new());
+            }
+            if defence.len() > moves.len() - i
+                && !is_futile::<P, T>(&pos, defence[0], &defence[1..], timeout)?
+            {
+                return flaw(i + 1, FlawKind::LongerDefence, defence);
+            }
+            pos.make_move(m);
+        }
+    }
+    if moves.len() % 2 == 0 {
+        let refutation = solve::<P, T>(pos, timeout)?;
+        return flaw(moves.len(), FlawKind::EndsWithDefence, refutation);
+    }
+    if !defence.is_empty() {
+        return flaw(moves.len(), FlawKind::NotMate, defence);
+    }
+    Ok(None)
+}
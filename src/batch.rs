@@ -0,0 +1,208 @@
+use crate::report::{Format, Report, Status};
+use crate::{search, sfen_label, Args};
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant};
+use tsumeshogi_solver::format::output;
+use tsumeshogi_solver::parse::{parse_sfen, position_at, ParseError};
+
+const SLOWEST: usize = 5;
+
+struct Outcome {
+    input: String,
+    status: Status,
+    elapsed: Duration,
+    line: String,
+}
+
+// 各 worker が自前の CancelableSearcher で解き、結果は入力順に出力する
+pub fn run(inputs: &[String], jobs: usize, args: &Args) -> Result<(), ParseError> {
+    let now = Instant::now();
+    let next = AtomicUsize::new(0);
+    let (tx, rx) = mpsc::channel();
+    let mut summary = Summary::default();
+    thread::scope(|s| {
+        for _ in 0..jobs.max(1) {
+            let (tx, next) = (tx.clone(), &next);
+            s.spawn(move || loop {
+                let i = next.fetch_add(1, Ordering::Relaxed);
+                let Some(input) = inputs.get(i) else {
+                    break;
+                };
+                if tx.send((i, solve(input.trim(), args))).is_err() {
+                    break;
+                }
+            });
+        }
+        drop(tx);
+        let mut pending = BTreeMap::new();
+        let mut printed = 0;
+        for (i, outcome) in rx {
+            pending.insert(i, outcome);
+            while let Some(outcome) = pending.remove(&printed) {
+                println!("{}", outcome.line);
+                summary.add(outcome.input, outcome.status, outcome.elapsed);
+                printed += 1;
+            }
+        }
+    });
+    summary.total = now.elapsed();
+    // JSON Lines の出力を汚さないよう、JSON 出力時の集計は標準エラー出力へ
+    match args.format {
+        Format::Text => println!("{summary}"),
+        Format::Json => eprintln!("{summary}"),
+    }
+    Ok(())
+}
+
+fn solve(input: &str, args: &Args) -> Outcome {
+    let now = Instant::now();
+    let pos = parse_sfen(input).and_then(|record| {
+        let len = record.moves().len();
+        let ply = args.ply.unwrap_or(len);
+        position_at(&record, ply).ok_or(ParseError::PlyOutOfRange(ply, len))
+    });
+    let report = match &pos {
+        Ok(pos) => {
+            let (result, searcher) = search(pos, args.timeout.map(Duration::from_secs_f32));
+            let report = Report::new(input, pos, &result, searcher.nodes(), now.elapsed());
+            if args.format == Format::Text {
+                let result = result.map(|v| output(pos, v, args.output_format()).join(" "));
+                return Outcome {
+                    input: input.to_string(),
+                    status: report.status,
+                    elapsed: now.elapsed(),
+                    line: format!("{}: {result:?}", sfen_label(input, args)),
+                };
+            }
+            report
+        }
+        Err(e) => Report::error(input, e),
+    };
+    let line = match args.format {
+        Format::Json => report
+            .to_json()
+            .unwrap_or_else(|e| format!("{input}: Err({e})")),
+        Format::Text => format!(
+            "{}: Err({})",
+            sfen_label(input, args),
+            report.error.unwrap_or_default()
+        ),
+    };
+    Outcome {
+        input: input.to_string(),
+        status: report.status,
+        elapsed: now.elapsed(),
+        line,
+    }
+}
+
+#[derive(Default)]
+struct Summary {
+    mate: usize,
+    nomate: usize,
+    timeout: usize,
+    nodelimit: usize,
+    canceled: usize,
+    error: usize,
+    total: Duration,
+    times: Vec<(Duration, String)>,
+}
+
+impl Summary {
+    fn add(&mut self, input: String, status: Status, elapsed: Duration) {
+        match status {
+            Status::Mate => self.mate += 1,
+            Status::NoMate => self.nomate += 1,
+            Status::Timeout => self.timeout += 1,
+            Status::NodeLimit => self.nodelimit += 1,
+            Status::Canceled => self.canceled += 1,
+            Status::Error => self.error += 1,
+        }
+        if status != Status::Error {
+            self.times.push((elapsed, input));
+        }
+    }
+    fn percentile(sorted: &[Duration], p: usize) -> Duration {
+        if sorted.is_empty() {
+            return Duration::ZERO;
+        }
+        sorted[((sorted.len() - 1) * p + 50) / 100]
+    }
+}
+
+impl std::fmt::Display for Summary {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut times = self.times.iter().map(|(d, _)| *d).collect::<Vec<_>>();
+        times.sort();
+        writeln!(
+            f,
+            "solved: {}, no mate: {}, timeout: {}, node limit: {}, canceled: {}, error: {}",
+            self.mate, self.nomate, self.timeout, self.nodelimit, self.canceled, self.error
+        )?;
+        writeln!(
+            f,
+            "elapsed: {:?} (sum {:?}), p50: {:?}, p90: {:?}, p99: {:?}, max: {:?}",
+            self.total,
+            times.iter().sum::<Duration>(),
+            Self::percentile(&times, 50),
+            Self::percentile(&times, 90),
+            Self::percentile(&times, 99),
+            Self::percentile(&times, 100),
+        )?;
+        let mut slowest = self.times.iter().collect::<Vec<_>>();
+        slowest.sort_by(|a, b| b.0.cmp(&a.0));
+        write!(f, "slowest:")?;
+        for (elapsed, input) in slowest.iter().take(SLOWEST) {
+            write!(f, "\n  {elapsed:?} {input}")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn summary() {
+        let mut summary = Summary::default();
+        for (i, status) in [
+            Status::Mate,
+            Status::NoMate,
+            Status::Timeout,
+            Status::NodeLimit,
+            Status::Canceled,
+            Status::Error,
+        ]
+        .into_iter()
+        .cycle()
+        .take(120)
+        .enumerate()
+        {
+            summary.add(i.to_string(), status, Duration::from_millis(i as u64));
+        }
+        assert_eq!(
+            (
+                summary.mate,
+                summary.nomate,
+                summary.timeout,
+                summary.nodelimit,
+                summary.canceled,
+                summary.error
+            ),
+            (20, 20, 20, 20, 20, 20)
+        );
+        let mut times = summary.times.iter().map(|(d, _)| *d).collect::<Vec<_>>();
+        times.sort();
+        assert_eq!(Summary::percentile(&times, 0), Duration::from_millis(0));
+        assert_eq!(Summary::percentile(&times, 100), Duration::from_millis(118));
+        let s = summary.to_string();
+        assert!(s.starts_with(
+            "solved: 20, no mate: 20, timeout: 20, node limit: 20, canceled: 20, error: 20"
+        ));
+        assert!(s.contains("\n  118ms 118\n  117ms 117\n  116ms 116\n  115ms 115\n  114ms 114"));
+    }
+}
@@ -0,0 +1,230 @@
+use dfpn::Node;
+use serde_json::Value;
+use shogi_core::{Move, PartialPosition, PieceKind, ToUsi};
+use shogi_legality_lite::all_legal_moves_partial;
+use shogi_usi_parser::FromUsi;
+use solver::attacks::in_check;
+use solver::ProofNode;
+use thiserror::Error;
+
+// 書き出された証明木を、yasai と置換表を使わずに検証する。
+// 合法手は shogi_legality_lite で生成し、王手の判定は solver::attacks で盤面から直接行う
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum CertifyError {
+    #[error("ply {ply}: {m} is not a legal move")]
+    Illegal { ply: usize, m: String },
+    #[error("ply {ply}: {m} is not a check")]
+    NotCheck { ply: usize, m: String },
+    #[error("ply {ply}: {m} is a pawn drop mate")]
+    DropPawnMate { ply: usize, m: String },
+    #[error("ply {0}: no checking move is given")]
+    NoCheck(usize),
+    #[error("ply {ply}: evasion {m} is not covered")]
+    Uncovered { ply: usize, m: String },
+    #[error("ply {0}: the subtree was cut off when exported")]
+    Truncated(usize),
+    #[error("ply {0}: invalid proof tree node")]
+    Invalid(usize),
+}
+
+// 証明木が詰みを証明していれば、その手数 (攻方は最短、玉方は最長) を返す
+pub fn certify(pos: &PartialPosition, tree: &ProofNode) -> Result<usize, CertifyError> {
+    if tree.node != Node::Or {
+        return Err(CertifyError::Invalid(0));
+    }
+    certify_or(pos, tree, 0)
+}
+
+// `--proof-tree` で書き出した JSON を読み、各ノードの usi をその局面の合法手と照合してから検証する。
+// 表示用の move は打つ手と盤上の手を区別できないことがあるので使わない
+pub fn certify_json(pos: &PartialPosition, value: &Value) -> Result<usize, CertifyError> {
+    let tree = from_json(pos, value, 0)?;
+    certify(pos, &tree)
+}
+
+fn from_json(pos: &PartialPosition, value: &Value, ply: usize) -> Result<ProofNode, CertifyError> {
+    let node = match value["node"].as_str() {
+        Some("or") => Node::Or,
+        Some("and") => Node::And,
+        _ => return Err(CertifyError::Invalid(ply)),
+    };
+    let legal_moves = all_legal_moves_partial(pos);
+    let mut children = Vec::new();
+    for child in value["children"].as_array().into_iter().flatten() {
+        let usi = child["usi"]
+            .as_str()
+            .ok_or(CertifyError::Invalid(ply + 1))?;
+        let m = match Move::from_usi(usi) {
+            Ok(m) if legal_moves.contains(&m) => m,
+            _ => {
+                return Err(CertifyError::Illegal {
+                    ply: ply + 1,
+                    m: usi.to_string(),
+                })
+            }
+        };
+        let mut next = pos.clone();
+        next.make_move(m);
+        let mut child = from_json(&next, child, ply + 1)?;
+        child.m = Some(m);
+        children.push(child);
+    }
+    Ok(ProofNode {
+        m: None,
+        node,
+        pn: value["pn"].as_u64().unwrap_or(u64::MAX),
+        dn: value["dn"].as_u64().unwrap_or(u64::MAX),
+        length: value["length"].as_u64().map(|len| len as usize),
+        truncated: value["truncated"].as_bool().unwrap_or(false),
+        children,
+    })
+}
+
+// 示された王手はすべて合法な王手で、その先が詰んでいなければならない
+fn certify_or(pos: &PartialPosition, tree: &ProofNode, ply: usize) -> Result<usize, CertifyError> {
+    if tree.truncated {
+        return Err(CertifyError::Truncated(ply));
+    }
+    if tree.children.is_empty() {
+        return Err(CertifyError::NoCheck(ply + 1));
+    }
+    let legal_moves = all_legal_moves_partial(pos);
+    let mut ret = usize::MAX;
+    for child in &tree.children {
+        let m = match child.m {
+            Some(m) if child.node == Node::And => m,
+            _ => return Err(CertifyError::Invalid(ply + 1)),
+        };
+        let usi = || m.to_usi_owned();
+        if !legal_moves.contains(&m) {
+            return Err(CertifyError::Illegal {
+                ply: ply + 1,
+                m: usi(),
+            });
+        }
+        let mut next = pos.clone();
+        next.make_move(m);
+        if !in_check(&next, next.side_to_move()) {
+            return Err(CertifyError::NotCheck {
+                ply: ply + 1,
+                m: usi(),
+            });
+        }
+        let len = certify_and(&next, child, ply + 1)?;
+        // 打歩詰は禁手
+        if len == 0
+            && matches!(m, Move::Drop { piece, .. } if piece.piece_kind() == PieceKind::Pawn)
+        {
+            return Err(CertifyError::DropPawnMate {
+                ply: ply + 1,
+                m: usi(),
+            });
+        }
+        ret = ret.min(len + 1);
+    }
+    Ok(ret)
+}
+
+// 合法な応手はすべて示され、そのどれにも詰みがなければならない。応手が無ければ詰み
+fn certify_and(pos: &PartialPosition, tree: &ProofNode, ply: usize) -> Result<usize, CertifyError> {
+    let legal_moves = all_legal_moves_partial(pos);
+    if legal_moves.is_empty() {
+        return Ok(0);
+    }
+    if tree.truncated {
+        return Err(CertifyError::Truncated(ply));
+    }
+    for child in &tree.children {
+        match child.m {
+            Some(m) if !legal_moves.contains(&m) => {
+                return Err(CertifyError::Illegal {
+                    ply: ply + 1,
+                    m: m.to_usi_owned(),
+                })
+            }
+            Some(_) if child.node == Node::Or => {}
+            _ => return Err(CertifyError::Invalid(ply + 1)),
+        }
+    }
+    let mut ret = 0;
+    for m in legal_moves {
+        let child = tree
+            .children
+            .iter()
+            .find(|child| child.m == Some(m))
+            .ok_or_else(|| CertifyError::Uncovered {
+                ply: ply + 1,
+                m: m.to_usi_owned(),
+            })?;
+        let mut next = pos.clone();
+        next.make_move(m);
+        ret = ret.max(certify_or(&next, child, ply + 1)? + 1);
+    }
+    Ok(ret)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::format::{proof_tree_json, OutputFormat};
+    use crate::parse::parse_sfen;
+    use solver::implementations::{HashMapTable, YasaiPosition};
+    use solver::{proof_tree, CancelableSearcher, TreeLimits};
+
+    #[test]
+    fn certify_proof_tree() {
+        let pos = parse_sfen("9/9/3pp4/+r2k1p3/2L1+p4/2+R6/B8/B8/9 b 4g4s4n3l14p 1")
+            .expect("failed to parse sfen")
+            .inner()
+            .clone();
+        let mut searcher: CancelableSearcher<YasaiPosition, HashMapTable> =
+            CancelableSearcher::new(YasaiPosition::from(pos.clone()), None);
+        searcher.dfpn_search().expect("canceled");
+        let tree = proof_tree(&mut searcher, TreeLimits::default());
+        assert_eq!(certify(&pos, &tree), Ok(3));
+        let mut json = proof_tree_json(&pos, &tree, OutputFormat::Kifu);
+        assert_eq!(certify_json(&pos, &json), Ok(3));
+        // 表示用の move は読まず、usi だけで手を決める
+        json["children"][0]["move"] = Value::Null;
+        assert_eq!(certify_json(&pos, &json), Ok(3));
+        json["children"][0]["usi"] = Value::Null;
+        assert_eq!(certify_json(&pos, &json), Err(CertifyError::Invalid(1)));
+
+        // 玉方の応手を 1 つ消すと、その応手が示されていないことになる
+        let mut broken = tree.clone();
+        let check = broken
+            .children
+            .iter_mut()
+            .find(|child| child.children.len() > 1)
+            .expect("no check with several evasions");
+        let removed = check
+            .children
+            .pop()
+            .and_then(|child| child.m)
+            .map(|m| m.to_usi_owned());
+        assert!(matches!(
+            certify(&pos, &broken),
+            Err(CertifyError::Uncovered { ply: 2, m }) if Some(m.as_str()) == removed.as_deref()
+        ));
+
+        // 王手でない手
+        let mut broken = tree.clone();
+        broken.children[0].m = Some(Move::from_usi("9h8i").expect("failed to parse move"));
+        assert!(matches!(
+            certify(&pos, &broken),
+            Err(CertifyError::NotCheck { ply: 1, .. })
+        ));
+
+        let tree = proof_tree(
+            &mut searcher,
+            TreeLimits {
+                depth: 1,
+                nodes: 1000,
+            },
+        );
+        assert!(matches!(
+            certify(&pos, &tree),
+            Err(CertifyError::Truncated(_))
+        ));
+    }
+}
@@ -0,0 +1,209 @@
+use dfpn::Node;
+use serde_json::{json, Value};
+use shogi_core::{Color, Move, PartialPosition, PieceKind, Square, ToUsi};
+use shogi_official_kifu::display_single_move_kansuji;
+use solver::{ProofNode, Refutation};
+use std::fmt::Write;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OutputFormat {
+    Usi,
+    Csa,
+    Kifu,
+}
+
+pub fn output(pos: &PartialPosition, v: Vec<Move>, format: OutputFormat) -> Vec<String> {
+    match format {
+        OutputFormat::Usi => v.iter().map(|m| m.to_usi_owned()).collect(),
+        OutputFormat::Csa => v
+            .iter()
+            .scan(pos.clone(), |pos, &m| {
+                let ret = move2csa(pos, m);
+                pos.make_move(m);
+                ret.ok()
+            })
+            .collect(),
+        OutputFormat::Kifu => v
+            .iter()
+            .scan(pos.clone(), |pos, &m| {
+                let ret = display_single_move_kansuji(pos, m);
+                pos.make_move(m);
+                ret
+            })
+            .collect(),
+    }
+}
+
+// 王手と逃れる応手を 1 行ずつ、深さに応じて字下げして並べる
+pub fn refutation_lines(
+    pos: &PartialPosition,
+    refutations: &[Refutation],
+    format: OutputFormat,
+) -> Vec<String> {
+    let mut ret = Vec::new();
+    for refutation in refutations {
+        let line = match refutation.escape {
+            Some(escape) => output(pos, vec![refutation.check, escape], format).join(" "),
+            None => {
+                let check = output(pos, vec![refutation.check], format).join(" ");
+                format!("{check} (no escape found)")
+            }
+        };
+        ret.push(line);
+        if let Some(escape) = refutation.escape {
+            let mut pos = pos.clone();
+            pos.make_move(refutation.check);
+            pos.make_move(escape);
+            ret.extend(
+                refutation_lines(&pos, &refutation.children, format)
+                    .into_iter()
+                    .map(|line| format!("  {line}")),
+            );
+        }
+    }
+    ret
+}
+
+// 証明木を Graphviz の DOT で書き出す。OR ノードは箱、AND ノードは楕円、打ち切った部分は破線
+pub fn proof_tree_dot(pos: &PartialPosition, tree: &ProofNode, format: OutputFormat) -> String {
+    let mut ret = String::from("digraph proof {\n    node [fontname=\"sans-serif\"];\n");
+    write_dot(pos, tree, format, &mut 0, &mut ret);
+    ret.push_str("}\n");
+    ret
+}
+
+fn write_dot(
+    pos: &PartialPosition,
+    tree: &ProofNode,
+    format: OutputFormat,
+    id: &mut usize,
+    sink: &mut String,
+) -> usize {
+    let this = *id;
+    *id += 1;
+    let mut label = format!("pn {} dn {}", number(tree.pn), number(tree.dn));
+    if let Some(length) = tree.length {
+        label += &format!("\\nmate in {length}");
+    }
+    let shape = match tree.node {
+        Node::Or => "box",
+        Node::And => "ellipse",
+    };
+    let style = if tree.truncated { ", style=dashed" } else { "" };
+    let _ = writeln!(
+        sink,
+        "    n{this} [label=\"{label}\", shape={shape}{style}];"
+    );
+    for child in &tree.children {
+        let Some(m) = child.m else { continue };
+        let label = output(pos, vec![m], format).join("");
+        let mut pos = pos.clone();
+        pos.make_move(m);
+        let child_id = write_dot(&pos, child, format, id, sink);
+        let _ = writeln!(sink, "    n{this} -> n{child_id} [label=\"{label}\"];");
+    }
+    this
+}
+
+// 証明木を入れ子の JSON にする。∞ は null。
+// move は表示用の表記、usi は打つ手と盤上の手を区別できる機械可読な表記
+pub fn proof_tree_json(pos: &PartialPosition, tree: &ProofNode, format: OutputFormat) -> Value {
+    tree_json(pos, tree, None, format)
+}
+
+fn tree_json(
+    pos: &PartialPosition,
+    tree: &ProofNode,
+    label: Option<String>,
+    format: OutputFormat,
+) -> Value {
+    let children = tree
+        .children
+        .iter()
+        .filter_map(|child| {
+            let m = child.m?;
+            let label = output(pos, vec![m], format).join("");
+            let mut pos = pos.clone();
+            pos.make_move(m);
+            Some(tree_json(&pos, child, Some(label), format))
+        })
+        .collect::<Vec<_>>();
+    json!({
+        "move": label,
+        "usi": tree.m.map(|m| m.to_usi_owned()),
+        "node": match tree.node {
+            Node::Or => "or",
+            Node::And => "and",
+        },
+        "pn": (tree.pn != u64::MAX).then_some(tree.pn),
+        "dn": (tree.dn != u64::MAX).then_some(tree.dn),
+        "length": tree.length,
+        "truncated": tree.truncated,
+        "children": children,
+    })
+}
+
+fn number(n: u64) -> String {
+    if n == u64::MAX {
+        String::from("∞")
+    } else {
+        n.to_string()
+    }
+}
+
+pub fn move2csa(pos: &PartialPosition, m: Move) -> Result<String, std::fmt::Error> {
+    let mut ret = String::new();
+    write_c(pos.side_to_move(), &mut ret)?;
+    match m {
+        Move::Normal { from, to, promote } => {
+            write_sq(from, &mut ret)?;
+            write_sq(to, &mut ret)?;
+            let pk = pos.piece_at(from).expect("no piece at `from`").piece_kind();
+            write_pk(
+                if promote {
+                    pk.promote().expect("piece kind is not promoted")
+                } else {
+                    pk
+                },
+                &mut ret,
+            )?;
+        }
+        Move::Drop { piece, to } => {
+            ret.write_str("00")?;
+            write_sq(to, &mut ret)?;
+            write_pk(piece.piece_kind(), &mut ret)?;
+        }
+    }
+    Ok(ret)
+}
+
+fn write_c<W: Write>(c: Color, sink: &mut W) -> Result<(), std::fmt::Error> {
+    match c {
+        Color::Black => sink.write_char('+'),
+        Color::White => sink.write_char('-'),
+    }
+}
+
+fn write_sq<W: Write>(sq: Square, sink: &mut W) -> Result<(), std::fmt::Error> {
+    sink.write_fmt(format_args!("{}{}", sq.file(), sq.rank()))
+}
+
+fn write_pk<W: Write>(pk: PieceKind, sink: &mut W) -> Result<(), std::fmt::Error> {
+    match pk {
+        PieceKind::Pawn => sink.write_str("FU")?,
+        PieceKind::Lance => sink.write_str("KY")?,
+        PieceKind::Knight => sink.write_str("KE")?,
+        PieceKind::Silver => sink.write_str("GI")?,
+        PieceKind::Gold => sink.write_str("KI")?,
+        PieceKind::Bishop => sink.write_str("KA")?,
+        PieceKind::Rook => sink.write_str("HI")?,
+        PieceKind::King => sink.write_str("OU")?,
+        PieceKind::ProPawn => sink.write_str("TO")?,
+        PieceKind::ProLance => sink.write_str("NY")?,
+        PieceKind::ProKnight => sink.write_str("NK")?,
+        PieceKind::ProSilver => sink.write_str("NG")?,
+        PieceKind::ProBishop => sink.write_str("UM")?,
+        PieceKind::ProRook => sink.write_str("RY")?,
+    }
+    Ok(())
+}
@@ -0,0 +1,9 @@
+pub mod certify;
+pub mod format;
+pub mod parse;
+mod problem;
+mod solve;
+
+pub use self::problem::Problem;
+pub use self::solve::{Solution, Solver, SolverBuilder, TableKind};
+pub use solver::{CanceledError, Rules};
@@ -1,160 +1,553 @@
-use clap::{ArgEnum, Parser};
-use encoding_rs::SHIFT_JIS;
-use shogi_core::{Color, Move, PartialPosition, PieceKind, Position, Square, ToUsi};
+mod batch;
+mod play;
+mod progress;
+mod regression;
+mod report;
+mod serve;
+mod verify;
+
+use clap::{ArgEnum, CommandFactory, ErrorKind, Parser, Subcommand};
+use report::{Format, Report};
+use shogi_core::{Move, PartialPosition, Position, ToUsi};
 use shogi_kifu_converter::converter::ToCsa;
-use shogi_kifu_converter::error::{ConvertError, CoreConvertError, NormalizerError};
 use shogi_kifu_converter::jkf::JsonKifuFormat;
-use shogi_kifu_converter::parser::{parse_csa_str, parse_kif_str};
-use shogi_official_kifu::display_single_move_kansuji;
-use shogi_usi_parser::FromUsi;
 use solver::implementations::{HashMapTable, YasaiPosition};
-use solver::solve;
-use std::fmt::Write;
+use solver::{
+    best_solution, is_mating_move, proof_tree, refute, CancelableSearcher, CanceledError, Hinter,
+    Progress, TreeLimits,
+};
 use std::fs::File;
 use std::io::{BufRead, Read};
+use std::path::{Path, PathBuf};
 use std::time::{Duration, Instant};
-use thiserror::Error;
-
-#[derive(Error, Debug)]
-enum ParseError {
-    #[error(transparent)]
-    Usi(#[from] shogi_usi_parser::Error),
-    #[error(transparent)]
-    Utf8(#[from] std::str::Utf8Error),
-    #[error(transparent)]
-    Io(#[from] std::io::Error),
-    #[error(transparent)]
-    Convert(#[from] ConvertError),
-    #[error(transparent)]
-    Normalize(#[from] NormalizerError),
-    #[error(transparent)]
-    CoreConvert(#[from] CoreConvertError),
-    #[error(transparent)]
-    KifError(#[from] KifError),
-}
-
-#[derive(Error, Debug)]
-enum KifError {
-    #[error("Input is not SHIFT-JIS")]
-    EncodingNotShiftJISError,
-    #[error("Decode error")]
-    DecodingError,
-}
-
-trait Parse {
-    fn parse(&self, input: &[u8]) -> Result<PartialPosition, ParseError>;
-}
-
-struct CsaParser;
-
-impl Parse for CsaParser {
-    fn parse(&self, input: &[u8]) -> Result<PartialPosition, ParseError> {
-        let jkf = parse_csa_str(std::str::from_utf8(input)?)?;
-        let pos = Position::try_from(&jkf)?;
-        Ok(pos.initial_position().clone())
-    }
-}
-
-struct KifParser;
-
-impl Parse for KifParser {
-    fn parse(&self, input: &[u8]) -> Result<PartialPosition, ParseError> {
-        let (cow, encoding_used, had_errors) = SHIFT_JIS.decode(input);
-        if encoding_used != SHIFT_JIS {
-            return Err(ParseError::from(KifError::EncodingNotShiftJISError));
-        }
-        if had_errors {
-            return Err(ParseError::from(KifError::DecodingError));
-        }
-        let jkf = parse_kif_str(&cow)?;
-        let pos = Position::try_from(&jkf)?;
-        Ok(pos.initial_position().clone())
-    }
-}
+use tsumeshogi_solver::format::{
+    output, proof_tree_dot, proof_tree_json, refutation_lines, OutputFormat,
+};
+use tsumeshogi_solver::parse::{
+    decode, detect, parse_sfen, parser, position_at, InputEncoding, InputFormat, Parse, ParseError,
+    Record,
+};
 
 #[derive(Parser)]
 #[clap(name = "Tsumeshogi Solver")]
 #[clap(version)]
+#[clap(subcommand_negates_reqs = true)]
 struct Args {
     /// Verbose mode
     #[clap(short, long)]
     verbose: bool,
     /// Input format
-    #[clap(short, long, arg_enum, value_name = "FORMAT", default_value_t = InputFormat::Sfen)]
-    input_format: InputFormat,
+    #[clap(short, long, arg_enum, value_name = "FORMAT", default_value_t = InputFormatArg::Sfen)]
+    input_format: InputFormatArg,
+    /// Character encoding of input files
+    #[clap(short, long, arg_enum, value_name = "ENCODING", default_value_t = InputEncodingArg::Auto)]
+    encoding: InputEncodingArg,
     /// Output format
-    #[clap(short, long, arg_enum, value_name = "FORMAT", default_value_t = OutputFormat::Usi)]
-    output_format: OutputFormat,
+    #[clap(short, long, arg_enum, value_name = "FORMAT", default_value_t = OutputFormatArg::Usi)]
+    output_format: OutputFormatArg,
+    /// Report format (`json` prints one JSON object per line)
+    #[clap(short, long, arg_enum, value_name = "FORMAT", default_value_t = Format::Text)]
+    format: Format,
+    /// Print USI `info` lines (depth, nodes, nps, current path, root pn/dn) while searching (to stderr with `-f json`)
+    #[clap(long, conflicts_with_all = &["hint", "all_plies", "jobs"])]
+    usi_info: bool,
     /// Time limit to solve (seconds)
     #[clap(short, long)]
     timeout: Option<f32>,
-    /// Input files or SFEN strings
+    /// Solve the position after N moves of a game record
+    #[clap(long, value_name = "N", conflicts_with_all = &["last", "all_plies"])]
+    ply: Option<usize>,
+    /// Solve the position after the last move of a game record
+    #[clap(long, conflicts_with = "all_plies")]
+    last: bool,
+    /// Report every position of a game record where the side to move had a forced mate
+    #[clap(long)]
+    all_plies: bool,
+    /// Show the defender's escape from every check when there is no mate, up to DEPTH attacking moves
+    #[clap(long, value_name = "DEPTH")]
+    refutation: Option<usize>,
+    /// Show only the first N plies of the solution and the remaining mate length
+    #[clap(long, value_name = "N", conflicts_with = "all_plies")]
+    hint: Option<usize>,
+    /// Solve SFEN inputs (`-i sfen` only) in batch with N worker threads and print a summary
+    #[clap(short, long, value_name = "N", conflicts_with = "all_plies")]
+    jobs: Option<usize>,
+    /// Continue the search from a transposition table saved by `--save-table` (hash map table only)
+    #[clap(long, value_name = "FILE", conflicts_with_all = &["hint", "all_plies", "jobs"])]
+    load_table: Option<PathBuf>,
+    /// Save the transposition table to FILE when the search ends, even on timeout (hash map table only)
+    #[clap(long, value_name = "FILE", conflicts_with_all = &["hint", "all_plies", "jobs"])]
+    save_table: Option<PathBuf>,
+    /// Also save the table every SECS seconds while searching
+    #[clap(long, value_name = "SECS", requires = "save_table", value_parser = positive_seconds)]
+    checkpoint: Option<f32>,
+    /// Write the proof tree to FILE as JSON (`.json`) or Graphviz DOT (any other extension)
+    #[clap(long, value_name = "FILE", conflicts_with_all = &["hint", "all_plies", "jobs"])]
+    proof_tree: Option<PathBuf>,
+    /// Maximum depth of the exported proof tree
+    #[clap(long, value_name = "N", default_value_t = TreeLimits::default().depth)]
+    tree_depth: usize,
+    /// Maximum number of nodes in the exported proof tree
+    #[clap(long, value_name = "N", default_value_t = TreeLimits::default().nodes)]
+    tree_nodes: usize,
+    /// Input files, SFEN strings or USI position commands
     #[clap(required(true))]
     inputs: Vec<String>,
+    #[clap(subcommand)]
+    command: Option<Command>,
 }
 
-#[derive(Clone, ArgEnum)]
-enum InputFormat {
+// ライブラリの列挙型に clap を持ち込まないよう、コマンドライン用の値はここで対応づける
+#[derive(Clone, Copy, ArgEnum)]
+enum InputFormatArg {
+    Auto,
     Sfen,
     Csa,
     Kif,
+    Bod,
+    Jkf,
+}
+
+impl From<InputFormatArg> for InputFormat {
+    fn from(format: InputFormatArg) -> Self {
+        match format {
+            InputFormatArg::Auto => InputFormat::Auto,
+            InputFormatArg::Sfen => InputFormat::Sfen,
+            InputFormatArg::Csa => InputFormat::Csa,
+            InputFormatArg::Kif => InputFormat::Kif,
+            InputFormatArg::Bod => InputFormat::Bod,
+            InputFormatArg::Jkf => InputFormat::Jkf,
+        }
+    }
+}
+
+#[derive(Clone, Copy, ArgEnum)]
+enum InputEncodingArg {
+    Auto,
+    Utf8,
+    ShiftJis,
+}
+
+impl From<InputEncodingArg> for InputEncoding {
+    fn from(encoding: InputEncodingArg) -> Self {
+        match encoding {
+            InputEncodingArg::Auto => InputEncoding::Auto,
+            InputEncodingArg::Utf8 => InputEncoding::Utf8,
+            InputEncodingArg::ShiftJis => InputEncoding::ShiftJis,
+        }
+    }
 }
 
 #[derive(Clone, Copy, ArgEnum)]
-enum OutputFormat {
+enum OutputFormatArg {
     Usi,
     Csa,
     Kifu,
 }
 
+impl From<OutputFormatArg> for OutputFormat {
+    fn from(format: OutputFormatArg) -> Self {
+        match format {
+            OutputFormatArg::Usi => OutputFormat::Usi,
+            OutputFormatArg::Csa => OutputFormat::Csa,
+            OutputFormatArg::Kifu => OutputFormat::Kifu,
+        }
+    }
+}
+
+impl Args {
+    fn input_format(&self) -> InputFormat {
+        self.input_format.into()
+    }
+    fn encoding(&self) -> InputEncoding {
+        self.encoding.into()
+    }
+    fn output_format(&self) -> OutputFormat {
+        self.output_format.into()
+    }
+}
+
+fn positive_seconds(s: &str) -> Result<f32, String> {
+    match s.parse::<f32>() {
+        Ok(secs) if secs > 0.0 && Duration::try_from_secs_f32(secs).is_ok() => Ok(secs),
+        _ => Err(format!("`{s}` is not a positive number of seconds")),
+    }
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Solve problems with expected answers (`sfen<TAB>length<TAB>usi-moves`) and report mismatches
+    VerifySet {
+        /// Problem file (`-` for stdin)
+        file: String,
+    },
+    /// Check a claimed solution: checks, legal defences, final mate, longest defence and no 余詰
+    Verify {
+        /// SFEN string or USI position command
+        position: String,
+        /// Moves in USI, CSA or KIF notation
+        #[clap(required(true), allow_hyphen_values = true)]
+        moves: Vec<String>,
+    },
+    /// Check a proof tree exported with `--proof-tree` (JSON) without the solver's move generator or table
+    CheckTree {
+        /// SFEN string or USI position command
+        position: String,
+        /// Proof tree file
+        file: PathBuf,
+    },
+    /// Practice a problem: enter attacking moves (USI, CSA or kansuji) and the program defends
+    Play {
+        /// SFEN string or USI position command
+        position: String,
+    },
+    /// Serve `POST /solve` over HTTP on the loopback interface
+    Serve {
+        /// Port to listen on
+        #[clap(short, long, default_value_t = 8080)]
+        port: u16,
+        /// Number of requests solved concurrently (defaults to the number of CPUs)
+        #[clap(short, long, value_name = "N")]
+        jobs: Option<usize>,
+    },
+}
+
 fn main() -> Result<(), ParseError> {
     let args = Args::parse();
-    match args.input_format {
+    // 並列に解くのは 1 行 1 局面の SFEN 入力だけ
+    if args.jobs.is_some() && args.input_format() != InputFormat::Sfen {
+        Args::command()
+            .error(
+                ErrorKind::ArgumentConflict,
+                "--jobs can only be used with `--input-format sfen`",
+            )
+            .exit();
+    }
+    let timeout = args.timeout.map(Duration::from_secs_f32);
+    if let Some(command) = &args.command {
+        let ok = match command {
+            Command::VerifySet { file } => regression::run(file, timeout)?,
+            Command::Verify { position, moves } => {
+                verify::run(position, moves, timeout, args.output_format())?
+            }
+            Command::CheckTree { position, file } => verify::check_tree(position, file)?,
+            Command::Play { position } => {
+                play::run(position, timeout, args.output_format())?;
+                true
+            }
+            Command::Serve { port, jobs } => {
+                let jobs = jobs
+                    .unwrap_or_else(|| std::thread::available_parallelism().map_or(1, usize::from));
+                serve::run(*port, jobs, timeout)?;
+                true
+            }
+        };
+        if !ok {
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+    match args.input_format() {
+        InputFormat::Auto => run_auto(&args),
         InputFormat::Sfen => run_sfen(&args),
-        InputFormat::Csa => run_parse(CsaParser, &args),
-        InputFormat::Kif => run_parse(KifParser, &args),
+        format => run_parse(parser(format, args.encoding())?.as_ref(), &args),
+    }
+}
+
+fn run_auto(args: &Args) -> Result<(), ParseError> {
+    for input in &args.inputs {
+        handle_error(run_auto_input(input, args), input, args)?;
+    }
+    Ok(())
+}
+
+fn run_auto_input(input: &str, args: &Args) -> Result<(), ParseError> {
+    let path = Path::new(input);
+    let (buf, path) = if input == "-" {
+        let mut buf = Vec::new();
+        std::io::stdin().lock().read_to_end(&mut buf)?;
+        (buf, None)
+    } else if path.is_file() {
+        (std::fs::read(path)?, Some(path))
+    } else {
+        (input.as_bytes().to_vec(), None)
+    };
+    match detect(path, &buf, args.encoding())
+        .ok_or_else(|| ParseError::UnknownFormat(input.to_string()))?
+    {
+        InputFormat::Sfen => {
+            for line in decode(&buf, args.encoding())?.lines() {
+                let line = line.trim();
+                if !line.is_empty() {
+                    let result =
+                        parse_sfen(line).and_then(|pos| run_record(&pos, line, args, true));
+                    handle_error(result, line, args)?;
+                }
+            }
+            Ok(())
+        }
+        format => run_records(&parser(format, args.encoding())?.parse(&buf)?, input, args),
     }
 }
 
 fn run_sfen(args: &Args) -> Result<(), ParseError> {
+    if let Some(jobs) = args.jobs {
+        let inputs = if args.inputs == ["-"] {
+            std::io::stdin()
+                .lock()
+                .lines()
+                .filter(|line| !line.as_ref().is_ok_and(|line| line.trim().is_empty()))
+                .collect::<Result<Vec<_>, _>>()?
+        } else {
+            args.inputs.clone()
+        };
+        return batch::run(&inputs, jobs, args);
+    }
     if args.inputs == ["-"] {
         let stdin = std::io::stdin();
         for line in stdin.lock().lines() {
-            let usi = format!("sfen {}", line?);
-            let pos = PartialPosition::from_usi(&usi)?;
-            run(&pos, &usi, args)?;
+            let line = line?;
+            let label = sfen_label(&line, args);
+            let result = parse_sfen(&line).and_then(|pos| run_record(&pos, &label, args, true));
+            handle_error(result, &label, args)?;
         }
     } else {
         for input in &args.inputs {
-            let usi = format!("sfen {}", input.trim());
-            let pos = PartialPosition::from_usi(&usi)?;
-            run(&pos, &usi, args)?;
+            let label = sfen_label(input, args);
+            let result = parse_sfen(input).and_then(|pos| run_record(&pos, &label, args, true));
+            handle_error(result, &label, args)?;
         }
     }
     Ok(())
 }
 
-fn run_parse<T>(parser: T, args: &Args) -> Result<(), ParseError>
+// テキスト出力では従来どおり `sfen <局面>:` の形で表示する。USI コマンドはそのまま
+fn sfen_label(input: &str, args: &Args) -> String {
+    let input = input.trim();
+    if args.format == Format::Json
+        || ["sfen", "position", "startpos"]
+            .iter()
+            .any(|p| input.starts_with(p))
+    {
+        input.to_string()
+    } else {
+        format!("sfen {input}")
+    }
+}
+
+fn run_parse<T>(parser: &T, args: &Args) -> Result<(), ParseError>
 where
-    T: Parse,
+    T: Parse + ?Sized,
 {
     if args.inputs == ["-"] {
         let stdin = std::io::stdin();
         let mut buf = Vec::new();
         stdin.lock().read_to_end(&mut buf)?;
-        run(&parser.parse(&buf)?, "-", args)?
+        run_records(&parser.parse(&buf)?, "-", args)?
     } else {
         for input in &args.inputs {
-            let mut buf = Vec::new();
-            let mut file = File::open(input)?;
-            file.read_to_end(&mut buf)?;
-            run(&parser.parse(&buf)?, input, args)?
+            let result =
+                read_file(input).and_then(|buf| run_records(&parser.parse(&buf)?, input, args));
+            handle_error(result, input, args)?;
+        }
+    }
+    Ok(())
+}
+
+fn read_file(path: &str) -> Result<Vec<u8>, ParseError> {
+    let mut buf = Vec::new();
+    File::open(path)?.read_to_end(&mut buf)?;
+    Ok(buf)
+}
+
+// JSON 出力では入力ごとのエラーも 1 行として出力し、残りの入力の処理を続ける
+fn handle_error(
+    result: Result<(), ParseError>,
+    input: &str,
+    args: &Args,
+) -> Result<(), ParseError> {
+    match result {
+        Err(e) if args.format == Format::Json => {
+            println!("{}", Report::error(input, &e).to_json()?);
+            Ok(())
+        }
+        result => result,
+    }
+}
+
+// 複数の棋譜を含む場合は番号と表題を付けて出力する
+fn run_records(records: &[Record], input: &str, args: &Args) -> Result<(), ParseError> {
+    for (i, record) in records.iter().enumerate() {
+        let mut label = input.to_string();
+        if records.len() > 1 {
+            label += &format!("#{}", i + 1);
+        }
+        if let Some(title) = &record.title {
+            label += &format!(" [{title}]");
+        }
+        handle_error(
+            run_record(&record.position, &label, args, false),
+            &label,
+            args,
+        )?;
+    }
+    Ok(())
+}
+
+// USI の指し手付き局面は指し手適用後、棋譜ファイルは開始局面を既定で解く
+fn run_record(record: &Position, input: &str, args: &Args, last: bool) -> Result<(), ParseError> {
+    if args.all_plies {
+        return scan(record, input, args);
+    }
+    let len = record.moves().len();
+    let ply = args.ply.unwrap_or(if args.last || last { len } else { 0 });
+    let pos = position_at(record, ply).ok_or(ParseError::PlyOutOfRange(ply, len))?;
+    run(&pos, input, args)
+}
+
+type Searcher = CancelableSearcher<YasaiPosition, HashMapTable>;
+
+const PROGRESS_INTERVAL: Duration = Duration::from_secs(1);
+
+fn search(
+    pos: &PartialPosition,
+    timeout: Option<Duration>,
+) -> (Result<Vec<Move>, CanceledError>, Searcher) {
+    let mut searcher = Searcher::new(YasaiPosition::from(pos.clone()), timeout);
+    let result = searcher.dfpn_search().map(|_| best_solution(&mut searcher));
+    (result, searcher)
+}
+
+// --load-table の置換表から続きを探索し、終了時と --checkpoint ごとに --save-table へ書き出す。
+// -v (テキスト出力時) と --usi-info では探索中の途中経過も表示する
+fn search_with_args(
+    pos: &PartialPosition,
+    timeout: Option<Duration>,
+    args: &Args,
+) -> Result<(Result<Vec<Move>, CanceledError>, Searcher), ParseError> {
+    let status = args.verbose && args.format == Format::Text;
+    if args.load_table.is_none() && args.save_table.is_none() && !status && !args.usi_info {
+        return Ok(search(pos, timeout));
+    }
+    let mut searcher = Searcher::new(YasaiPosition::from(pos.clone()), None);
+    if status || args.usi_info {
+        let usi_info = args.usi_info;
+        let json = args.format == Format::Json;
+        searcher.set_observer(PROGRESS_INTERVAL, move |p: &Progress| {
+            if status {
+                progress::print_status(p);
+            }
+            if usi_info {
+                progress::print_usi_info(p, json);
+            }
+        });
+    }
+    if let Some(path) = &args.load_table {
+        searcher.load_table(path)?;
+    }
+    let interval = args.checkpoint.map(Duration::from_secs_f32);
+    let now = Instant::now();
+    let result = loop {
+        let remaining = timeout.map(|t| t.saturating_sub(now.elapsed()));
+        let slice = match (remaining, interval) {
+            (Some(remaining), Some(interval)) => Some(remaining.min(interval)),
+            (remaining, interval) => remaining.or(interval),
+        };
+        searcher.set_timeout(slice);
+        match searcher.dfpn_search() {
+            // 全体の制限時間ではなく保存間隔で打ち切った場合は、保存して続ける
+            Err(CanceledError::Timeout) if slice != remaining => {
+                if let Some(path) = &args.save_table {
+                    searcher.save_table(path)?;
+                }
+            }
+            result => break result,
+        }
+    };
+    if status {
+        eprintln!();
+    }
+    let result = result.map(|_| best_solution(&mut searcher));
+    if let Some(path) = &args.save_table {
+        searcher.save_table(path)?;
+    }
+    Ok((result, searcher))
+}
+
+// 棋譜中の各局面で手番側に詰みがあったか、実際の指し手で詰ませに行けたかを報告する
+fn scan(record: &Position, input: &str, args: &Args) -> Result<(), ParseError> {
+    let timeout = args.timeout.map(Duration::from_secs_f32);
+    let mut pos = record.initial_position().clone();
+    for ply in 0..=record.moves().len() {
+        let played = record.moves().get(ply).copied();
+        let now = Instant::now();
+        let (result, searcher) = search(&pos, timeout);
+        let elapsed = now.elapsed();
+        let found = match (&result, played) {
+            (Ok(v), Some(m)) if !v.is_empty() => Some(if v[0] == m {
+                Ok(true)
+            } else {
+                is_mating_move::<YasaiPosition, HashMapTable>(&pos, m, timeout)
+            }),
+            _ => None,
+        };
+        match args.format {
+            Format::Json => {
+                if !matches!(&result, Ok(v) if v.is_empty()) {
+                    let mut report = Report::new(input, &pos, &result, searcher.nodes(), elapsed);
+                    report.ply = Some(ply);
+                    report.played = played.map(|m| m.to_usi_owned());
+                    report.found = found.and_then(Result::ok);
+                    println!("{}", report.to_json()?);
+                }
+            }
+            Format::Text => match result {
+                Ok(v) if v.is_empty() => {}
+                Ok(v) => {
+                    let verdict = match (played, found) {
+                        (Some(m), Some(found)) => {
+                            let played = output(&pos, vec![m], args.output_format()).join(" ");
+                            match found {
+                                Ok(true) => format!("played {played}: found"),
+                                Ok(false) => format!("played {played}: missed"),
+                                Err(e) => format!("played {played}: {e}"),
+                            }
+                        }
+                        _ => String::from("not played"),
+                    };
+                    let len = v.len();
+                    let moves = output(&pos, v, args.output_format()).join(" ");
+                    println!("{input} ply {ply}: mate in {len} ({moves}), {verdict}");
+                }
+                Err(e) => println!("{input} ply {ply}: {e}"),
+            },
+        }
+        if let Some(m) = played {
+            pos.make_move(m);
         }
     }
     Ok(())
 }
 
 fn run(pos: &PartialPosition, input: &str, args: &Args) -> Result<(), ParseError> {
+    let timeout = args.timeout.map(Duration::from_secs_f32);
+    if args.format == Format::Json {
+        let now = Instant::now();
+        let (result, mut searcher) = search_with_args(pos, timeout, args)?;
+        let mut report = Report::new(input, pos, &result, searcher.nodes(), now.elapsed());
+        if let Some(plies) = args.hint {
+            report.truncate(plies);
+        }
+        #[cfg(feature = "stats")]
+        {
+            report.stats = Some(searcher.stats().into());
+        }
+        println!("{}", report.to_json()?);
+        if let Some(path) = &args.proof_tree {
+            export_proof_tree(pos, &mut searcher, path, args)?;
+        }
+        return Ok(());
+    }
     print!("{}: ", input);
     if args.verbose {
         let jkf = JsonKifuFormat::try_from(&Position::arbitrary_position(pos.clone()))?;
@@ -162,93 +555,65 @@ fn run(pos: &PartialPosition, input: &str, args: &Args) -> Result<(), ParseError
         println!("{}", jkf.to_csa_owned());
     }
     let now = Instant::now();
-    let result = solve::<YasaiPosition, HashMapTable>(
-        pos.clone(),
-        args.timeout.map(Duration::from_secs_f32),
-    )
-    .map(|v| output(pos, v, args.output_format).join(" "));
+    if let Some(plies) = args.hint {
+        let mut hinter = Hinter::<YasaiPosition, HashMapTable>::new(pos.clone(), timeout);
+        match hinter.hint(plies) {
+            Ok(hint) => {
+                let moves = output(pos, hint.moves, args.output_format()).join(" ");
+                println!("Ok({:?}), {} more plies to mate", moves, hint.remaining);
+            }
+            Err(e) => println!("Err({:?})", e),
+        }
+        if args.verbose {
+            println!("elapsed: {:?}", now.elapsed());
+        }
+        return Ok(());
+    }
+    let (result, mut searcher) = search_with_args(pos, timeout, args)?;
+    // 反証の探索で置換表を引いた分は含めない
+    #[cfg(feature = "stats")]
+    let stats = report::Stats::from(searcher.stats());
+    let no_mate = matches!(&result, Ok(v) if v.is_empty());
+    let result = result.map(|v| output(pos, v, args.output_format()).join(" "));
     println!("{:?}", result);
+    if let Some(depth) = args.refutation.filter(|_| no_mate) {
+        let refutations = refute(&mut searcher, depth);
+        for line in refutation_lines(pos, &refutations, args.output_format()) {
+            println!("  {line}");
+        }
+    }
+    if let Some(path) = &args.proof_tree {
+        export_proof_tree(pos, &mut searcher, path, args)?;
+    }
     if args.verbose {
         println!("elapsed: {:?}", now.elapsed());
+        if args.save_table.is_some() {
+            println!("table: {} entries", searcher.table().len());
+        }
+        #[cfg(feature = "stats")]
+        println!("stats: {stats}");
     }
     Ok(())
 }
 
-fn output(pos: &PartialPosition, v: Vec<Move>, format: OutputFormat) -> Vec<String> {
-    match format {
-        OutputFormat::Usi => v.iter().map(|m| m.to_usi_owned()).collect(),
-        OutputFormat::Csa => v
-            .iter()
-            .scan(pos.clone(), |pos, &m| {
-                let ret = move2csa(pos, m);
-                pos.make_move(m);
-                ret.ok()
-            })
-            .collect(),
-        OutputFormat::Kifu => v
-            .iter()
-            .scan(pos.clone(), |pos, &m| {
-                let ret = display_single_move_kansuji(pos, m);
-                pos.make_move(m);
-                ret
-            })
-            .collect(),
-    }
-}
-
-fn move2csa(pos: &PartialPosition, m: Move) -> Result<String, std::fmt::Error> {
-    let mut ret = String::new();
-    write_c(pos.side_to_move(), &mut ret)?;
-    match m {
-        Move::Normal { from, to, promote } => {
-            write_sq(from, &mut ret)?;
-            write_sq(to, &mut ret)?;
-            let pk = pos.piece_at(from).expect("no piece at `from`").piece_kind();
-            write_pk(
-                if promote {
-                    pk.promote().expect("piece kind is not promoted")
-                } else {
-                    pk
-                },
-                &mut ret,
-            )?;
-        }
-        Move::Drop { piece, to } => {
-            ret.write_str("00")?;
-            write_sq(to, &mut ret)?;
-            write_pk(piece.piece_kind(), &mut ret)?;
-        }
-    }
-    Ok(ret)
-}
-
-fn write_c<W: Write>(c: Color, sink: &mut W) -> Result<(), std::fmt::Error> {
-    match c {
-        Color::Black => sink.write_char('+'),
-        Color::White => sink.write_char('-'),
-    }
-}
-
-fn write_sq<W: Write>(sq: Square, sink: &mut W) -> Result<(), std::fmt::Error> {
-    sink.write_fmt(format_args!("{}{}", sq.file(), sq.rank()))
-}
-
-fn write_pk<W: Write>(pk: PieceKind, sink: &mut W) -> Result<(), std::fmt::Error> {
-    match pk {
-        PieceKind::Pawn => sink.write_str("FU")?,
-        PieceKind::Lance => sink.write_str("KY")?,
-        PieceKind::Knight => sink.write_str("KE")?,
-        PieceKind::Silver => sink.write_str("GI")?,
-        PieceKind::Gold => sink.write_str("KI")?,
-        PieceKind::Bishop => sink.write_str("KA")?,
-        PieceKind::Rook => sink.write_str("HI")?,
-        PieceKind::King => sink.write_str("OU")?,
-        PieceKind::ProPawn => sink.write_str("TO")?,
-        PieceKind::ProLance => sink.write_str("NY")?,
-        PieceKind::ProKnight => sink.write_str("NK")?,
-        PieceKind::ProSilver => sink.write_str("NG")?,
-        PieceKind::ProBishop => sink.write_str("UM")?,
-        PieceKind::ProRook => sink.write_str("RY")?,
-    }
+// 拡張子が .json なら JSON、それ以外は DOT で書き出す
+fn export_proof_tree(
+    pos: &PartialPosition,
+    searcher: &mut Searcher,
+    path: &Path,
+    args: &Args,
+) -> Result<(), ParseError> {
+    let limits = TreeLimits {
+        depth: args.tree_depth,
+        nodes: args.tree_nodes,
+    };
+    let tree = proof_tree(searcher, limits);
+    let contents = match path.extension().and_then(|ext| ext.to_str()) {
+        Some(ext) if ext.eq_ignore_ascii_case("json") => {
+            serde_json::to_string_pretty(&proof_tree_json(pos, &tree, args.output_format()))?
+        }
+        _ => proof_tree_dot(pos, &tree, args.output_format()),
+    };
+    std::fs::write(path, contents)?;
     Ok(())
 }
@@ -0,0 +1,127 @@
+mod bod;
+mod csa;
+mod detect;
+mod encoding;
+mod jkf;
+mod kif;
+mod moves;
+mod sfen;
+
+pub use self::bod::BodParser;
+pub use self::csa::CsaParser;
+pub use self::detect::detect;
+pub use self::encoding::{decode, DecodeError, InputEncoding};
+pub use self::jkf::JkfParser;
+pub use self::kif::KifParser;
+pub use self::moves::parse_moves;
+pub use self::sfen::parse_sfen;
+
+use shogi_core::{PartialPosition, Position};
+use shogi_kifu_converter::error::{ConvertError, CoreConvertError, NormalizerError};
+use solver::TableFileError;
+use std::str::FromStr;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum ParseError {
+    #[error(transparent)]
+    Usi(#[from] shogi_usi_parser::Error),
+    #[error(transparent)]
+    Decode(#[from] DecodeError),
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[error(transparent)]
+    Convert(#[from] ConvertError),
+    #[error(transparent)]
+    Normalize(#[from] NormalizerError),
+    #[error(transparent)]
+    CoreConvert(#[from] CoreConvertError),
+    #[error(transparent)]
+    BodError(#[from] BodError),
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+    #[error(transparent)]
+    Table(#[from] TableFileError),
+    #[error("Cannot detect input format: {0}")]
+    UnknownFormat(String),
+    #[error("Illegal move: {0}")]
+    IllegalMove(String),
+    #[error("Ply {0} is out of range (the record has {1} moves)")]
+    PlyOutOfRange(usize, usize),
+    #[error("Invalid timeout: {0}")]
+    InvalidTimeout(String),
+    #[error("No record parser for {0:?} input")]
+    NoParser(InputFormat),
+}
+
+#[derive(Error, Debug)]
+pub enum BodError {
+    #[error("Board diagram not found")]
+    BoardNotFound,
+    #[error("Invalid board row: {0}")]
+    InvalidRow(String),
+    #[error("Invalid hand: {0}")]
+    InvalidHand(String),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum InputFormat {
+    Auto,
+    Sfen,
+    Csa,
+    Kif,
+    Bod,
+    Jkf,
+}
+
+impl FromStr for InputFormat {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "auto" => Ok(InputFormat::Auto),
+            "sfen" => Ok(InputFormat::Sfen),
+            "csa" => Ok(InputFormat::Csa),
+            "kif" => Ok(InputFormat::Kif),
+            "bod" => Ok(InputFormat::Bod),
+            "jkf" => Ok(InputFormat::Jkf),
+            _ => Err(ParseError::UnknownFormat(s.to_string())),
+        }
+    }
+}
+
+pub struct Record {
+    pub title: Option<String>,
+    pub position: Position,
+}
+
+pub trait Parse {
+    fn parse(&self, input: &[u8]) -> Result<Vec<Record>, ParseError>;
+}
+
+// 棋譜形式のパーサ。Auto は detect で形式を決めてから、SFEN は parse_sfen で 1 行ずつ読む
+pub fn parser(format: InputFormat, encoding: InputEncoding) -> Result<Box<dyn Parse>, ParseError> {
+    match format {
+        InputFormat::Csa => Ok(Box::new(CsaParser::new(encoding))),
+        InputFormat::Kif => Ok(Box::new(KifParser::new(encoding))),
+        InputFormat::Bod => Ok(Box::new(BodParser::new(encoding))),
+        InputFormat::Jkf => Ok(Box::new(JkfParser::new(encoding))),
+        InputFormat::Auto | InputFormat::Sfen => Err(ParseError::NoParser(format)),
+    }
+}
+
+// 棋譜の ply 手目を指した後の局面
+pub fn position_at(record: &Position, ply: usize) -> Option<PartialPosition> {
+    let mut pos = record.initial_position().clone();
+    for &m in record.moves().get(..ply)? {
+        pos.make_move(m)?;
+    }
+    Some(pos)
+}
+
+fn header_value(text: &str, key: &str) -> Option<String> {
+    text.lines()
+        .find_map(|line| line.trim().strip_prefix(key))
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty())
+}
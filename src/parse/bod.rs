@@ -0,0 +1,307 @@
+use super::kif::split_records;
+use super::{decode, header_value, BodError, InputEncoding, Parse, ParseError, Record};
+use shogi_core::{Color, Hand, PartialPosition, Piece, PieceKind, Position, Square};
+
+// 柿木形式の局面図 (BOD) だけが貼り付けられた入力を読む
+#[derive(Default)]
+pub struct BodParser {
+    encoding: InputEncoding,
+}
+
+impl BodParser {
+    pub fn new(encoding: InputEncoding) -> Self {
+        Self { encoding }
+    }
+}
+
+impl Parse for BodParser {
+    fn parse(&self, input: &[u8]) -> Result<Vec<Record>, ParseError> {
+        let text = decode(input, self.encoding)?;
+        split_records(&text)
+            .into_iter()
+            .map(|text| {
+                Ok(Record {
+                    title: header_value(text, "表題："),
+                    position: Position::arbitrary_position(parse_bod(text)?),
+                })
+            })
+            .collect()
+    }
+}
+
+fn parse_bod(text: &str) -> Result<PartialPosition, BodError> {
+    let mut pos = PartialPosition::empty();
+    let mut rank = 0;
+    let (mut black_hand, mut white_hand, mut bare_hand) = (None, None, None);
+    let mut side_to_move = None;
+    for line in text.lines().map(str::trim) {
+        if let Some(row) = line.strip_prefix('|') {
+            rank += 1;
+            parse_row(&mut pos, rank, row)?;
+        } else if let Some((color, s)) = hand_line(line) {
+            let hand = parse_hand(s)?;
+            match color {
+                Some(Color::Black) => black_hand = hand,
+                Some(Color::White) => white_hand = hand,
+                None => bare_hand = hand,
+            }
+        } else if line.starts_with("先手番") || line.starts_with("下手番") {
+            side_to_move = Some(Color::Black);
+        } else if line.starts_with("後手番") || line.starts_with("上手番") {
+            side_to_move = Some(Color::White);
+        } else if let Some(s) = line.strip_prefix("手数") {
+            let n = s
+                .trim_start_matches(|c: char| c == '＝' || c == '=')
+                .split(|c: char| !c.is_ascii_digit())
+                .next()
+                .and_then(|s| s.parse::<u16>().ok());
+            if let Some(n) = n {
+                pos.ply_set(n + 1);
+            }
+        }
+    }
+    if rank != 9 {
+        return Err(BodError::BoardNotFound);
+    }
+    // 手番の指定が無ければ先手を攻方とみなす
+    let side = side_to_move.unwrap_or(Color::Black);
+    pos.side_to_move_set(side);
+    let (attacker, defender) = match side {
+        Color::Black => (black_hand, white_hand),
+        Color::White => (white_hand, black_hand),
+    };
+    let attacker = attacker.or(bare_hand).unwrap_or_else(Hand::new);
+    // 玉方の持駒が省略されている場合は残り駒全部
+    let defender = defender.unwrap_or_else(|| remaining(&pos, attacker));
+    *pos.hand_of_a_player_mut(side) = attacker;
+    *pos.hand_of_a_player_mut(side.flip()) = defender;
+    Ok(pos)
+}
+
+fn parse_row(pos: &mut PartialPosition, rank: u8, row: &str) -> Result<(), BodError> {
+    let invalid = || BodError::InvalidRow(row.to_string());
+    let cells = row.split('|').next().unwrap_or_default();
+    let mut file = 9;
+    let mut color = Color::Black;
+    for c in cells.chars() {
+        let piece_kind = match c {
+            'v' | 'V' => {
+                color = Color::White;
+                continue;
+            }
+            '・' => None,
+            c if c.is_whitespace() || c == '^' => continue,
+            c => Some(board_piece_kind(c).ok_or_else(invalid)?),
+        };
+        let sq = Square::new(file, rank).ok_or_else(invalid)?;
+        pos.piece_set(sq, piece_kind.map(|pk| Piece::new(pk, color)));
+        color = Color::Black;
+        file -= 1;
+    }
+    if file != 0 {
+        return Err(invalid());
+    }
+    Ok(())
+}
+
+// 「先手の持駒：」「後手の持ち駒:」「持駒　なし」などの表記揺れを許容する
+fn hand_line(line: &str) -> Option<(Option<Color>, &str)> {
+    let (head, tail) = line
+        .split_once("持ち駒")
+        .or_else(|| line.split_once("持駒"))?;
+    let color = match head.trim().trim_end_matches('の') {
+        "先手" | "下手" | "▲" | "☗" => Some(Color::Black),
+        "後手" | "上手" | "△" | "☖" => Some(Color::White),
+        "" => None,
+        _ => return None,
+    };
+    Some((
+        color,
+        tail.trim_start_matches(|c: char| c == '：' || c == ':' || c.is_whitespace()),
+    ))
+}
+
+// 「残り全部」などは省略と同じく None
+fn parse_hand(s: &str) -> Result<Option<Hand>, BodError> {
+    let s = s.trim();
+    if s.starts_with("残り") {
+        return Ok(None);
+    }
+    let invalid = || BodError::InvalidHand(s.to_string());
+    let mut hand = Hand::new();
+    if s.is_empty() || s.starts_with("なし") {
+        return Ok(Some(hand));
+    }
+    let mut entries: Vec<(PieceKind, String)> = Vec::new();
+    for c in s.chars() {
+        if let Some(pk) = hand_piece_kind(c) {
+            entries.push((pk, String::new()));
+        } else if c.is_whitespace() || matches!(c, '、' | ',' | '，') {
+            continue;
+        } else if let Some((_, n)) = entries.last_mut() {
+            n.push(c);
+        } else {
+            return Err(invalid());
+        }
+    }
+    for (pk, n) in entries {
+        let count = if n.is_empty() {
+            1
+        } else {
+            parse_number(&n).ok_or_else(invalid)?
+        };
+        for _ in 0..count {
+            hand = hand.added(pk).ok_or_else(invalid)?;
+        }
+    }
+    Ok(Some(hand))
+}
+
+// 漢数字 (十八 など) と算用数字の両方を受け付ける
+fn parse_number(s: &str) -> Option<u8> {
+    let (mut ret, mut digits) = (0_u8, None::<u8>);
+    for c in s.chars() {
+        if c == '十' {
+            ret = ret.checked_add(digits.take().unwrap_or(1).checked_mul(10)?)?;
+        } else {
+            let d = "〇一二三四五六七八九"
+                .chars()
+                .position(|k| k == c)
+                .or_else(|| "０１２３４５６７８９".chars().position(|k| k == c))
+                .or_else(|| c.to_digit(10).map(|d| d as usize))? as u8;
+            digits = Some(digits.unwrap_or(0).checked_mul(10)?.checked_add(d)?);
+        }
+    }
+    ret.checked_add(digits.unwrap_or(0))
+}
+
+fn hand_piece_kind(c: char) -> Option<PieceKind> {
+    match c {
+        '歩' => Some(PieceKind::Pawn),
+        '香' => Some(PieceKind::Lance),
+        '桂' => Some(PieceKind::Knight),
+        '銀' => Some(PieceKind::Silver),
+        '金' => Some(PieceKind::Gold),
+        '角' => Some(PieceKind::Bishop),
+        '飛' => Some(PieceKind::Rook),
+        _ => None,
+    }
+}
+
+fn board_piece_kind(c: char) -> Option<PieceKind> {
+    match c {
+        '玉' | '王' => Some(PieceKind::King),
+        'と' => Some(PieceKind::ProPawn),
+        '杏' => Some(PieceKind::ProLance),
+        '圭' => Some(PieceKind::ProKnight),
+        '全' => Some(PieceKind::ProSilver),
+        '馬' => Some(PieceKind::ProBishop),
+        '龍' | '竜' => Some(PieceKind::ProRook),
+        c => hand_piece_kind(c),
+    }
+}
+
+fn remaining(pos: &PartialPosition, used: Hand) -> Hand {
+    let mut hand = Hand::new();
+    for (pk, total) in [
+        (PieceKind::Pawn, 18),
+        (PieceKind::Lance, 4),
+        (PieceKind::Knight, 4),
+        (PieceKind::Silver, 4),
+        (PieceKind::Gold, 4),
+        (PieceKind::Bishop, 2),
+        (PieceKind::Rook, 2),
+    ] {
+        let mut count = used.count(pk).unwrap_or_default();
+        for file in 1..=9 {
+            for rank in 1..=9 {
+                if let Some(p) = Square::new(file, rank).and_then(|sq| pos.piece_at(sq)) {
+                    if p.piece_kind().unpromote().unwrap_or(p.piece_kind()) == pk {
+                        count += 1;
+                    }
+                }
+            }
+        }
+        for _ in count..total {
+            hand = hand.added(pk).unwrap_or(hand);
+        }
+    }
+    hand
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use encoding_rs::SHIFT_JIS;
+    use shogi_usi_parser::FromUsi;
+
+    fn sfen(s: &str) -> PartialPosition {
+        PartialPosition::from_usi(&format!("sfen {s}")).expect("failed to parse sfen")
+    }
+
+    #[test]
+    fn without_defender_hand() {
+        let input = "
+  ９ ８ ７ ６ ５ ４ ３ ２ １
++---------------------------+
+| ・ ・ ・ ・ ・ ・ ・ ・ ・|一
+| ・ ・ ・ ・ ・ ・ ・ ・ ・|二
+| ・ ・ ・v歩v歩 ・ ・ ・ ・|三
+|v龍 ・ ・v玉 ・v歩 ・ ・ ・|四
+| ・ ・ 香 ・vと ・ ・ ・ ・|五
+| ・ ・ 龍 ・ ・ ・ ・ ・ ・|六
+| 角 ・ ・ ・ ・ ・ ・ ・ ・|七
+| 角 ・ ・ ・ ・ ・ ・ ・ ・|八
+| ・ ・ ・ ・ ・ ・ ・ ・ ・|九
++---------------------------+
+先手の持駒：なし
+";
+        let pos = parse_bod(input).expect("failed to parse");
+        assert_eq!(
+            pos,
+            sfen("9/9/3pp4/+r2k1p3/2L1+p4/2+R6/B8/B8/9 b 4g4s4n3l14p 1")
+        );
+    }
+
+    #[test]
+    fn shift_jis_with_kanji_numerals() {
+        let input = "
+後手の持ち駒:飛　金二
++---------------------------+
+| ・ ・ ・ ・v玉 ・ ・ ・ ・|一
+| ・ ・ ・ ・ ・ ・ ・ ・ ・|二
+| ・ ・ ・ ・ ・ ・ ・ ・ ・|三
+| ・ ・ ・ ・ ・ ・ ・ ・ ・|四
+| ・ ・ ・ ・ ・ ・ ・ ・ ・|五
+| ・ ・ ・ ・ ・ ・ ・ ・ ・|六
+| ・ ・ ・ ・ ・ ・ ・ ・ ・|七
+| ・ ・ ・ ・ ・ ・ ・ ・ ・|八
+| ・ ・ ・ ・ 玉 ・ ・ ・ ・|九
++---------------------------+
+先手の持駒：歩十八
+後手番
+";
+        let (bytes, _, _) = SHIFT_JIS.encode(input);
+        let records = BodParser::default().parse(&bytes).expect("failed to parse");
+        let pos = records[0].position.inner();
+        assert_eq!(pos, &sfen("4k4/9/9/9/9/9/9/9/4K4 w 18Pr2g 1"));
+    }
+
+    #[test]
+    fn bare_hand_line() {
+        let input = "
+持駒　金
+| ・ ・ ・ ・v玉 ・ ・ ・ ・|一
+| ・ ・ ・ ・ ・ ・ ・ ・ ・|二
+| ・ ・ ・ ・ 歩 ・ ・ ・ ・|三
+| ・ ・ ・ ・ ・ ・ ・ ・ ・|四
+| ・ ・ ・ ・ ・ ・ ・ ・ ・|五
+| ・ ・ ・ ・ ・ ・ ・ ・ ・|六
+| ・ ・ ・ ・ ・ ・ ・ ・ ・|七
+| ・ ・ ・ ・ ・ ・ ・ ・ ・|八
+| ・ ・ ・ ・ ・ ・ ・ ・ ・|九
+";
+        let pos = parse_bod(input).expect("failed to parse");
+        assert_eq!(pos, sfen("4k4/9/4P4/9/9/9/9/9/9 b G2r2b3g4s4n4l17p 1"));
+    }
+}
@@ -0,0 +1,59 @@
+use super::{decode, header_value, InputEncoding, Parse, ParseError, Record};
+use shogi_core::Position;
+use shogi_kifu_converter::parser::parse_csa_str;
+
+#[derive(Default)]
+pub struct CsaParser {
+    encoding: InputEncoding,
+}
+
+impl CsaParser {
+    pub fn new(encoding: InputEncoding) -> Self {
+        Self { encoding }
+    }
+}
+
+impl Parse for CsaParser {
+    fn parse(&self, input: &[u8]) -> Result<Vec<Record>, ParseError> {
+        let text = decode(input, self.encoding)?;
+        split_records(&text)
+            .into_iter()
+            .map(|text| {
+                let jkf = parse_csa_str(text)?;
+                Ok(Record {
+                    title: header_value(text, "$EVENT:"),
+                    position: Position::try_from(&jkf)?,
+                })
+            })
+            .collect()
+    }
+}
+
+// 複数の棋譜は `/` だけの行で区切られる
+fn split_records(text: &str) -> Vec<&str> {
+    let mut records = Vec::new();
+    let (mut start, mut offset) = (0, 0);
+    for line in text.split_inclusive('\n') {
+        if line.trim() == "/" {
+            records.push(&text[start..offset]);
+            start = offset + line.len();
+        }
+        offset += line.len();
+    }
+    records.push(&text[start..]);
+    records.retain(|s| !s.trim().is_empty());
+    records
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn split() {
+        let text = "V2.2\n$EVENT:1\nPI\n+\n/\n$EVENT:2\nPI\n-\n/\n";
+        let records = split_records(text);
+        assert_eq!(records, ["V2.2\n$EVENT:1\nPI\n+\n", "$EVENT:2\nPI\n-\n"]);
+        assert_eq!(header_value(records[1], "$EVENT:"), Some(String::from("2")));
+    }
+}
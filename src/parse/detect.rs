@@ -0,0 +1,99 @@
+use super::{decode, InputEncoding, InputFormat};
+use std::path::Path;
+
+// 拡張子で判別できなければ内容から推測する。内容はパーサと同じ encoding で読む
+pub fn detect(path: Option<&Path>, input: &[u8], encoding: InputEncoding) -> Option<InputFormat> {
+    let ext = path
+        .and_then(Path::extension)
+        .and_then(|ext| ext.to_str())
+        .map(str::to_ascii_lowercase);
+    match ext.as_deref() {
+        Some("sfen") => return Some(InputFormat::Sfen),
+        Some("csa") => return Some(InputFormat::Csa),
+        Some("kif" | "kifu") => return Some(InputFormat::Kif),
+        Some("bod") => return Some(InputFormat::Bod),
+        Some("jkf" | "json") => return Some(InputFormat::Jkf),
+        _ => {}
+    }
+    let text = decode(input, encoding).ok()?;
+    let text = text.trim_start();
+    if text.starts_with('{') {
+        Some(InputFormat::Jkf)
+    } else if ["sfen ", "position", "startpos"]
+        .iter()
+        .any(|prefix| text.starts_with(prefix))
+    {
+        Some(InputFormat::Sfen)
+    } else if text
+        .lines()
+        .any(|line| line.starts_with("P1") || line.starts_with("PI"))
+    {
+        Some(InputFormat::Csa)
+    } else if ["手数----", "後手の持駒", "上手の持駒"]
+        .iter()
+        .any(|marker| text.contains(marker))
+    {
+        // 局面図付きの KIF も局面図だけの BOD も KIF として読む。BOD のパーサは拡張子か -i bod で選ぶ
+        Some(InputFormat::Kif)
+    } else if text
+        .split_whitespace()
+        .next()
+        .is_some_and(|board| board.matches('/').count() == 8)
+    {
+        Some(InputFormat::Sfen)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn by_content() {
+        let test_cases = [
+            (
+                "9/9/3pp4/+r2k1p3/2L1+p4/2+R6/B8/B8/9 b 4g4s4n3l14p 1",
+                Some(InputFormat::Sfen),
+            ),
+            ("position startpos moves 7g7f", Some(InputFormat::Sfen)),
+            ("V2.2\nPI\n+\n", Some(InputFormat::Csa)),
+            (
+                "手合割：平手\n手数----指手---------消費時間--\n",
+                Some(InputFormat::Kif),
+            ),
+            ("後手の持駒：なし\n", Some(InputFormat::Kif)),
+            ("上手の持駒：なし\n", Some(InputFormat::Kif)),
+            ("{\"header\":{}}", Some(InputFormat::Jkf)),
+            ("hello", None),
+        ];
+        for (input, expected) in test_cases {
+            assert_eq!(
+                detect(None, input.as_bytes(), InputEncoding::Auto),
+                expected,
+                "{input}"
+            );
+        }
+    }
+
+    #[test]
+    fn with_encoding() {
+        let (sjis, _, _) = encoding_rs::SHIFT_JIS.encode("後手の持駒：なし\n");
+        assert_eq!(
+            detect(None, &sjis, InputEncoding::ShiftJis),
+            Some(InputFormat::Kif)
+        );
+        // 指定された encoding で読めなければ、推測で別の encoding を試さない
+        assert_eq!(detect(None, &sjis, InputEncoding::Utf8), None);
+    }
+
+    #[test]
+    fn by_extension() {
+        let path = Path::new("problems/001.KIFU");
+        assert_eq!(
+            detect(Some(path), b"", InputEncoding::Auto),
+            Some(InputFormat::Kif)
+        );
+    }
+}
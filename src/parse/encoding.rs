@@ -0,0 +1,128 @@
+use encoding_rs::{DecoderResult, Encoding, SHIFT_JIS, UTF_8};
+use std::borrow::Cow;
+use thiserror::Error;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum InputEncoding {
+    #[default]
+    Auto,
+    Utf8,
+    ShiftJis,
+}
+
+#[derive(Error, Debug)]
+#[error("Invalid {encoding} byte sequence at line {line} (byte offset {offset})")]
+pub struct DecodeError {
+    pub encoding: &'static str,
+    pub line: usize,
+    pub offset: usize,
+}
+
+// BOM → ヘッダでの宣言 (`#KIF version=2.0 encoding=UTF-8` など) → UTF-8 として妥当か、の順で判定する
+pub fn decode(input: &[u8], encoding: InputEncoding) -> Result<Cow<str>, DecodeError> {
+    match encoding {
+        InputEncoding::Utf8 => decode_with(strip_utf8_bom(input), UTF_8),
+        InputEncoding::ShiftJis => decode_with(input, SHIFT_JIS),
+        InputEncoding::Auto => {
+            if let Some((encoding, len)) = Encoding::for_bom(input) {
+                decode_with(&input[len..], encoding)
+            } else if let Some(encoding) = declared_encoding(input) {
+                decode_with(input, encoding)
+            } else if let Ok(s) = std::str::from_utf8(input) {
+                Ok(Cow::Borrowed(s))
+            } else {
+                decode_with(input, SHIFT_JIS)
+            }
+        }
+    }
+}
+
+fn strip_utf8_bom(input: &[u8]) -> &[u8] {
+    input.strip_prefix(b"\xEF\xBB\xBF").unwrap_or(input)
+}
+
+fn declared_encoding(input: &[u8]) -> Option<&'static Encoding> {
+    input
+        .split(|&b| b == b'\n')
+        .take(4)
+        .filter(|line| line.starts_with(b"#") || line.starts_with(b"'"))
+        .find_map(|line| {
+            let pos = line
+                .windows(9)
+                .position(|w| w.eq_ignore_ascii_case(b"encoding="))?;
+            let label = line[pos + 9..]
+                .split(|&b| b.is_ascii_whitespace() || b == b',')
+                .next()?;
+            Encoding::for_label(label)
+        })
+}
+
+fn decode_with<'a>(
+    input: &'a [u8],
+    encoding: &'static Encoding,
+) -> Result<Cow<'a, str>, DecodeError> {
+    if encoding == UTF_8 {
+        return std::str::from_utf8(input)
+            .map(Cow::Borrowed)
+            .map_err(|e| error(input, encoding, e.valid_up_to()));
+    }
+    let mut decoder = encoding.new_decoder_without_bom_handling();
+    let mut ret = String::new();
+    let mut read = 0;
+    loop {
+        ret.reserve(
+            decoder
+                .max_utf8_buffer_length_without_replacement(input.len() - read)
+                .unwrap_or(input.len() - read),
+        );
+        let (result, n) =
+            decoder.decode_to_string_without_replacement(&input[read..], &mut ret, true);
+        read += n;
+        match result {
+            DecoderResult::InputEmpty => return Ok(Cow::Owned(ret)),
+            DecoderResult::OutputFull => continue,
+            DecoderResult::Malformed(len, consumed) => {
+                return Err(error(
+                    input,
+                    encoding,
+                    read - usize::from(len) - usize::from(consumed),
+                ))
+            }
+        }
+    }
+}
+
+fn error(input: &[u8], encoding: &'static Encoding, offset: usize) -> DecodeError {
+    DecodeError {
+        encoding: encoding.name(),
+        line: input[..offset].iter().filter(|&&b| b == b'\n').count() + 1,
+        offset,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detect() {
+        let text = "表題：詰将棋\n";
+        let (sjis, _, _) = SHIFT_JIS.encode(text);
+        assert_eq!(decode(&sjis, InputEncoding::Auto).unwrap(), text);
+        assert_eq!(decode(text.as_bytes(), InputEncoding::Auto).unwrap(), text);
+        let bom = [&b"\xEF\xBB\xBF"[..], text.as_bytes()].concat();
+        assert_eq!(decode(&bom, InputEncoding::Auto).unwrap(), text);
+        let declared = format!("#KIF version=2.0 encoding=Shift_JIS\n{text}");
+        let (sjis, _, _) = SHIFT_JIS.encode(&declared);
+        assert_eq!(decode(&sjis, InputEncoding::Auto).unwrap(), declared);
+    }
+
+    #[test]
+    fn error_offset() {
+        let input = b"abc\nde\xFF\xFFf\n";
+        let e = decode(input, InputEncoding::Utf8).unwrap_err();
+        assert_eq!((e.line, e.offset), (2, 6));
+        let e = decode(input, InputEncoding::ShiftJis).unwrap_err();
+        assert_eq!((e.line, e.offset), (2, 6));
+    }
+}
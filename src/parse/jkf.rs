@@ -0,0 +1,24 @@
+use super::{decode, InputEncoding, Parse, ParseError, Record};
+use shogi_core::Position;
+use shogi_kifu_converter::jkf::JsonKifuFormat;
+
+#[derive(Default)]
+pub struct JkfParser {
+    encoding: InputEncoding,
+}
+
+impl JkfParser {
+    pub fn new(encoding: InputEncoding) -> Self {
+        Self { encoding }
+    }
+}
+
+impl Parse for JkfParser {
+    fn parse(&self, input: &[u8]) -> Result<Vec<Record>, ParseError> {
+        let jkf = serde_json::from_str::<JsonKifuFormat>(&decode(input, self.encoding)?)?;
+        Ok(vec![Record {
+            title: jkf.header.get("表題").cloned(),
+            position: Position::try_from(&jkf)?,
+        }])
+    }
+}
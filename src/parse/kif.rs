@@ -0,0 +1,162 @@
+use super::{decode, header_value, InputEncoding, Parse, ParseError, Record};
+use shogi_core::Position;
+use shogi_kifu_converter::parser::parse_kif_str;
+
+#[derive(Default)]
+pub struct KifParser {
+    encoding: InputEncoding,
+}
+
+impl KifParser {
+    pub fn new(encoding: InputEncoding) -> Self {
+        Self { encoding }
+    }
+}
+
+impl Parse for KifParser {
+    fn parse(&self, input: &[u8]) -> Result<Vec<Record>, ParseError> {
+        let text = decode(input, self.encoding)?;
+        split_records(&text)
+            .into_iter()
+            .map(|text| {
+                let jkf = parse_kif_str(text)?;
+                Ok(Record {
+                    title: header_value(text, "表題："),
+                    position: Position::try_from(&jkf)?,
+                })
+            })
+            .collect()
+    }
+}
+
+// 指し手の後に現れた局面図と `手数----`、またはそれらが続くヘッダ行と、
+// 局面図だけの棋譜の後に現れた対局者名以外のヘッダ行・局面図を、次の棋譜の始まりとみなす。
+// 指し手の後のヘッダ行だけ (`終了日時：` など) は同じ棋譜に含める
+pub(super) fn split_records(text: &str) -> Vec<&str> {
+    let lines = text.split_inclusive('\n').collect::<Vec<_>>();
+    let mut records = Vec::new();
+    let (mut start, mut offset) = (0, 0);
+    let (mut board_done, mut moves_started) = (false, false);
+    for (i, line) in lines.iter().enumerate() {
+        let l = line.trim();
+        let key = header_key(l);
+        let is_player = key.is_some_and(|key| {
+            ["先手", "後手", "上手", "下手"]
+                .iter()
+                .any(|player| key.starts_with(player))
+        });
+        let new_record = is_board(l)
+            || l.starts_with("手数----")
+            || (key.is_some() && starts_record(&lines[i..]));
+        if (moves_started && new_record)
+            || (board_done && !moves_started && (is_board(l) || (key.is_some() && !is_player)))
+        {
+            records.push(&text[start..offset]);
+            start = offset;
+            board_done = false;
+            moves_started = false;
+        }
+        if l.starts_with("+---") && text[start..offset].contains('|') {
+            board_done = true;
+        }
+        if l.starts_with("手数----") {
+            moves_started = true;
+        }
+        offset += line.len();
+    }
+    records.push(&text[start..]);
+    records.retain(|s| !s.trim().is_empty());
+    records
+}
+
+// 持駒と変化、コメント類を除いた `key：value` 形式のヘッダ行のキー
+fn header_key(line: &str) -> Option<&str> {
+    line.split_once('：').map(|(key, _)| key).filter(|key| {
+        !key.contains("持駒")
+            && !key.starts_with("変化")
+            && !key.starts_with(|c: char| matches!(c, '*' | '&' | '#'))
+    })
+}
+
+fn is_board(line: &str) -> bool {
+    line.starts_with("後手の持駒")
+        || line.starts_with("上手の持駒")
+        || line.starts_with("+---")
+        || line.starts_with('|')
+}
+
+// 空行を挟まずに続くヘッダ行 (とコメント) の後に、局面図か `手数----` が現れるか
+fn starts_record(lines: &[&str]) -> bool {
+    for line in lines {
+        let l = line.trim();
+        if is_board(l) || l.starts_with("手数----") {
+            return true;
+        }
+        if !(l.contains('：') || l.starts_with(|c: char| matches!(c, '*' | '&' | '#'))) {
+            return false;
+        }
+    }
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn split() {
+        let text = "表題：第1問
+後手の持駒：残り全部
++---------------------------+
+| ・ ・ ・ ・ ・ ・ ・ ・v玉|一
++---------------------------+
+先手の持駒：金
+表題：第2問
+後手の持駒：なし
++---------------------------+
+| ・ ・ ・ ・ ・ ・ ・ ・v玉|一
++---------------------------+
+先手の持駒：飛
+先手：詰方
+手数----指手---------消費時間--
+   1 ２二飛打
+手合割：平手
+手数----指手---------消費時間--
+   1 ７六歩(77)   ( 0:01/00:00:01)
+変化：1手
+   1 ２六歩(27)   ( 0:01/00:00:01)
+";
+        let records = split_records(text);
+        assert_eq!(records.len(), 3);
+        assert_eq!(
+            header_value(records[0], "表題："),
+            Some(String::from("第1問"))
+        );
+        assert_eq!(
+            header_value(records[1], "表題："),
+            Some(String::from("第2問"))
+        );
+        assert!(records[1].ends_with("２二飛打\n"));
+        assert!(records[2].starts_with("手合割：平手"));
+    }
+
+    #[test]
+    fn trailing_header() {
+        let text = "手合割：平手
+手数----指手---------消費時間--
+   1 ７六歩(77)   ( 0:01/00:00:01)
+終了日時：2024/01/01 10:00:00
+
+開始日時：2024/01/02 10:00:00
+手合割：平手
+手数----指手---------消費時間--
+   1 ２六歩(27)   ( 0:01/00:00:01)
+終了日時：2024/01/02 10:00:00
+";
+        let records = split_records(text);
+        assert_eq!(records.len(), 2);
+        assert!(records[0].contains("終了日時：2024/01/01"));
+        assert!(records[1].starts_with("開始日時：2024/01/02"));
+        assert!(records[1].ends_with("終了日時：2024/01/02 10:00:00\n"));
+    }
+}
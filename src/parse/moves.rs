@@ -0,0 +1,64 @@
+use super::ParseError;
+use crate::format::move2csa;
+use shogi_core::{Move, PartialPosition, ToUsi};
+use shogi_legality_lite::all_legal_moves_partial;
+use shogi_official_kifu::display_single_move_kansuji;
+
+// USI (`7g7f`), CSA (`+7776FU`), KIF (`▲７六歩`, `同　歩`) のいずれの表記も、その局面の合法手と照合して読む
+pub fn parse_moves(position: &PartialPosition, input: &str) -> Result<Vec<Move>, ParseError> {
+    let input = input.replace("同　", "同").replace("同 ", "同");
+    let mut pos = position.clone();
+    let mut ret = Vec::new();
+    for token in input.split_whitespace() {
+        let m = all_legal_moves_partial(&pos)
+            .into_iter()
+            .find(|&m| matches(&pos, m, token))
+            .ok_or_else(|| ParseError::IllegalMove(token.to_string()))?;
+        pos.make_move(m);
+        ret.push(m);
+    }
+    Ok(ret)
+}
+
+fn matches(pos: &PartialPosition, m: Move, token: &str) -> bool {
+    if m.to_usi_owned() == token {
+        return true;
+    }
+    if let Ok(csa) = move2csa(pos, m) {
+        if csa == token || csa[1..] == *token {
+            return true;
+        }
+    }
+    let kif = |s: &str| {
+        s.trim_start_matches(['▲', '△', '☗', '☖'])
+            .trim_end_matches('打')
+            .chars()
+            .filter(|c| !c.is_whitespace())
+            .collect::<String>()
+    };
+    display_single_move_kansuji(pos, m).is_some_and(|s| kif(&s) == kif(token))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse::parse_sfen;
+
+    #[test]
+    fn notations() {
+        let pos = parse_sfen("9/9/3pp4/+r2k1p3/2L1+p4/2+R6/B8/B8/9 b 4g4s4n3l14p 1")
+            .expect("failed to parse sfen");
+        let expected = ["7e7b+", "N*8f", "7f7c"];
+        for input in [
+            "7e7b+ N*8f 7f7c",
+            "+7572NY -0086KE +7673RY",
+            "7572NY 0086KE 7673RY",
+            "▲７二香成 △８六桂 ▲７三龍",
+            "７二香成　８六桂打　７三龍",
+        ] {
+            let moves = parse_moves(pos.inner(), input).expect("failed to parse moves");
+            let usi = moves.iter().map(|m| m.to_usi_owned()).collect::<Vec<_>>();
+            assert_eq!(usi, expected, "{input}");
+        }
+    }
+}
@@ -0,0 +1,59 @@
+use super::ParseError;
+use shogi_core::{Move, PartialPosition, Position};
+use shogi_legality_lite::is_legal_partial_lite;
+use shogi_usi_parser::FromUsi;
+
+// SFEN 文字列に加えて `position sfen ... moves ...` や `startpos moves ...` も受け付け、指し手も含めた棋譜として返す
+pub fn parse_sfen(input: &str) -> Result<Position, ParseError> {
+    let input = input.trim();
+    let input = input
+        .strip_prefix("position")
+        .map_or(input, str::trim_start);
+    let (sfen, moves) = match input.split_once("moves") {
+        Some((sfen, moves)) => (sfen.trim(), moves),
+        None => (input, ""),
+    };
+    let mut pos = if sfen == "startpos" {
+        Position::startpos()
+    } else {
+        let sfen = sfen.strip_prefix("sfen").map_or(sfen, str::trim_start);
+        Position::arbitrary_position(PartialPosition::from_usi(&format!("sfen {sfen}"))?)
+    };
+    for usi in moves.split_whitespace() {
+        let m = Move::from_usi(usi)?;
+        if !is_legal_partial_lite(pos.inner(), m) {
+            return Err(ParseError::IllegalMove(usi.to_string()));
+        }
+        pos.make_move(m);
+    }
+    Ok(pos)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use shogi_core::ToUsi;
+
+    #[test]
+    fn position_with_moves() {
+        let expected = "sfen lnsgkgsnl/1r5b1/pppppp1pp/6p2/9/2P6/PP1PPPPPP/1B5R1/LNSGKGSNL b - 3";
+        for input in [
+            "position startpos moves 7g7f 3c3d",
+            "startpos moves 7g7f 3c3d",
+            "position sfen lnsgkgsnl/1r5b1/ppppppppp/9/9/9/PPPPPPPPP/1B5R1/LNSGKGSNL b - 1 moves 7g7f 3c3d",
+            "lnsgkgsnl/1r5b1/ppppppppp/9/9/9/PPPPPPPPP/1B5R1/LNSGKGSNL b - 1 moves 7g7f 3c3d",
+            "sfen lnsgkgsnl/1r5b1/pppppp1pp/6p2/9/2P6/PP1PPPPPP/1B5R1/LNSGKGSNL b - 3",
+        ] {
+            let pos = parse_sfen(input).expect("failed to parse");
+            assert_eq!(pos.inner().to_usi_owned(), expected, "{input}");
+        }
+    }
+
+    #[test]
+    fn illegal_move() {
+        assert!(matches!(
+            parse_sfen("startpos moves 7g7e"),
+            Err(ParseError::IllegalMove(_))
+        ));
+    }
+}
@@ -0,0 +1,98 @@
+use dfpn::{Node, Position};
+use shogi_core::{Move, PartialPosition};
+use solver::implementations::{HashMapTable, YasaiPosition};
+use solver::{defend, solve, Defence};
+use std::io::{BufRead, Write};
+use std::time::Duration;
+use tsumeshogi_solver::format::{output, OutputFormat};
+use tsumeshogi_solver::parse::{parse_moves, parse_sfen, position_at, ParseError};
+
+// 攻方の手を入力させ、玉方は証明済みの最長の応手で応じる
+pub fn run(
+    position: &str,
+    timeout: Option<Duration>,
+    format: OutputFormat,
+) -> Result<(), ParseError> {
+    let record = parse_sfen(position)?;
+    let len = record.moves().len();
+    let mut pos = position_at(&record, len).ok_or(ParseError::PlyOutOfRange(len, len))?;
+    let solution = match solve::<YasaiPosition, HashMapTable>(pos.clone(), timeout) {
+        Ok(v) if v.is_empty() => {
+            println!("no mate");
+            return Ok(());
+        }
+        Ok(v) => v,
+        Err(e) => {
+            println!("{e}");
+            return Ok(());
+        }
+    };
+    println!("mate in {}", solution.len());
+    let stdin = std::io::stdin();
+    let mut lines = stdin.lock().lines();
+    let mut ply = 0;
+    loop {
+        print!("> ");
+        std::io::stdout().flush()?;
+        let Some(line) = lines.next().transpose()? else {
+            return Ok(());
+        };
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        if line == "quit" {
+            return Ok(());
+        }
+        let m = match parse_moves(&pos, line).as_deref() {
+            Ok([m]) => *m,
+            Ok(_) => {
+                println!("enter one move");
+                continue;
+            }
+            Err(e) => {
+                println!("{e}");
+                continue;
+            }
+        };
+        let mv = output(&pos, vec![m], format).join(" ");
+        if !is_check(&pos, m) {
+            println!("{mv}: not a check");
+            continue;
+        }
+        let mut next = pos.clone();
+        next.make_move(m);
+        let defence = match defend::<YasaiPosition, HashMapTable>(&next, timeout) {
+            Ok(defence) => defence,
+            Err(e) => {
+                println!("{e}");
+                continue;
+            }
+        };
+        match defence {
+            Defence::Escape(r) => {
+                let r = output(&next, vec![r], format).join(" ");
+                println!("{mv}: wrong, {r} escapes");
+            }
+            Defence::Mated(v) if v.is_empty() => {
+                ply += 1;
+                println!("{mv}: mate in {ply} (shortest {})", solution.len());
+                return Ok(());
+            }
+            Defence::Mated(v) => {
+                let reply = output(&next, vec![v[0]], format).join(" ");
+                println!("{mv} {reply} (mate in {} more plies)", v.len() - 1);
+                next.make_move(v[0]);
+                pos = next;
+                ply += 2;
+            }
+        }
+    }
+}
+
+fn is_check(pos: &PartialPosition, m: Move) -> bool {
+    YasaiPosition::from(pos.clone())
+        .generate_legal_moves(Node::Or)
+        .into_iter()
+        .any(|(c, _)| c == m)
+}
@@ -0,0 +1,95 @@
+use crate::parse::{
+    decode, detect, parse_sfen, parser, position_at, InputEncoding, InputFormat, ParseError,
+};
+use shogi_core::PartialPosition;
+use std::path::Path;
+use std::str::FromStr;
+
+// 解く対象の局面。SFEN は指し手適用後、棋譜は開始局面
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Problem {
+    pub title: Option<String>,
+    pub position: PartialPosition,
+}
+
+impl Problem {
+    pub fn from_sfen(input: &str) -> Result<Self, ParseError> {
+        let record = parse_sfen(input)?;
+        let len = record.moves().len();
+        Ok(Self {
+            title: None,
+            position: position_at(&record, len).ok_or(ParseError::PlyOutOfRange(len, len))?,
+        })
+    }
+    // 複数の問題を含む入力 (SFEN の複数行、CSA の `/` 区切りなど) はその数だけ返す
+    pub fn parse(
+        input: &[u8],
+        format: InputFormat,
+        encoding: InputEncoding,
+    ) -> Result<Vec<Self>, ParseError> {
+        Self::parse_with_path(None, input, format, encoding)
+    }
+    pub fn load<P: AsRef<Path>>(
+        path: P,
+        format: InputFormat,
+        encoding: InputEncoding,
+    ) -> Result<Vec<Self>, ParseError> {
+        let input = std::fs::read(path.as_ref())?;
+        Self::parse_with_path(Some(path.as_ref()), &input, format, encoding)
+    }
+    fn parse_with_path(
+        path: Option<&Path>,
+        input: &[u8],
+        format: InputFormat,
+        encoding: InputEncoding,
+    ) -> Result<Vec<Self>, ParseError> {
+        let format = match format {
+            InputFormat::Auto => detect(path, input, encoding).ok_or_else(|| {
+                ParseError::UnknownFormat(path.map_or(String::new(), |p| p.display().to_string()))
+            })?,
+            format => format,
+        };
+        match format {
+            InputFormat::Sfen => decode(input, encoding)?
+                .lines()
+                .filter(|line| !line.trim().is_empty())
+                .map(Self::from_sfen)
+                .collect(),
+            format => Ok(parser(format, encoding)?
+                .parse(input)?
+                .into_iter()
+                .map(|record| Self {
+                    title: record.title,
+                    position: record.position.initial_position().clone(),
+                })
+                .collect()),
+        }
+    }
+}
+
+impl FromStr for Problem {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::from_sfen(s)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_lines() {
+        let input = b"position startpos moves 7g7f\n\n9/9/3pp4/+r2k1p3/2L1+p4/2+R6/B8/B8/9 b 4g4s4n3l14p 1\n";
+        let problems =
+            Problem::parse(input, InputFormat::Auto, InputEncoding::Auto).expect("failed to parse");
+        assert_eq!(problems.len(), 2);
+        assert_eq!(
+            problems[1],
+            "9/9/3pp4/+r2k1p3/2L1+p4/2+R6/B8/B8/9 b 4g4s4n3l14p 1"
+                .parse()
+                .expect("failed to parse")
+        );
+    }
+}
@@ -0,0 +1,96 @@
+use shogi_core::ToUsi;
+use solver::Progress;
+use std::io::Write;
+
+// -v で標準エラーに上書き表示する 1 行
+pub fn status_line(progress: &Progress) -> String {
+    let path = progress
+        .path
+        .iter()
+        .map(|m| m.to_usi_owned())
+        .collect::<Vec<_>>()
+        .join(" ");
+    format!(
+        "{} nodes, {} nps, {} entries, pn {} dn {}, depth {}: {path}",
+        progress.nodes, progress.nps, progress.entries, progress.pn, progress.dn, progress.depth
+    )
+}
+
+// USI の info コマンド。string は行末まで続くので証明数・反証数は別の行にする
+pub fn usi_info(progress: &Progress) -> Vec<String> {
+    let mut info = format!(
+        "info depth {} time {} nodes {} nps {}",
+        progress.depth,
+        progress.elapsed.as_millis(),
+        progress.nodes,
+        progress.nps
+    );
+    if let Some(hashfull) = progress.hashfull {
+        info += &format!(" hashfull {hashfull}");
+    }
+    if !progress.path.is_empty() {
+        info += " pv";
+        for m in &progress.path {
+            info += &format!(" {}", m.to_usi_owned());
+        }
+    }
+    vec![
+        info,
+        format!("info string pn {} dn {}", progress.pn, progress.dn),
+    ]
+}
+
+pub fn print_status(progress: &Progress) {
+    let mut stderr = std::io::stderr().lock();
+    // 前の表示を消してから書き直す
+    let _ = write!(stderr, "\r\x1b[2K{}", status_line(progress));
+    let _ = stderr.flush();
+}
+
+// JSON Lines の出力に混ざらないよう、-f json では stderr に出す
+pub fn print_usi_info(progress: &Progress, stderr: bool) {
+    for line in usi_info(progress) {
+        if stderr {
+            eprintln!("{line}");
+        } else {
+            println!("{line}");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use shogi_core::Move;
+    use shogi_usi_parser::FromUsi;
+    use std::time::Duration;
+
+    #[test]
+    fn info_lines() {
+        let progress = Progress {
+            nodes: 1500,
+            nps: 3000,
+            elapsed: Duration::from_millis(500),
+            entries: 1000,
+            hashfull: None,
+            pn: 2,
+            dn: 5,
+            depth: 2,
+            path: ["7e7b+", "N*8f"]
+                .iter()
+                .map(|s| Move::from_usi(s).expect("failed to parse move"))
+                .collect(),
+        };
+        assert_eq!(
+            usi_info(&progress),
+            vec![
+                "info depth 2 time 500 nodes 1500 nps 3000 pv 7e7b+ N*8f",
+                "info string pn 2 dn 5",
+            ]
+        );
+        assert_eq!(
+            status_line(&progress),
+            "1500 nodes, 3000 nps, 1000 entries, pn 2 dn 5, depth 2: 7e7b+ N*8f"
+        );
+    }
+}
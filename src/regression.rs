@@ -0,0 +1,36 @@
+use solver::implementations::{HashMapTable, YasaiPosition};
+use solver::{verify_case, RegressionCase};
+use std::io::Read;
+use std::time::Duration;
+use tsumeshogi_solver::parse::ParseError;
+
+// 期待する答え付きの問題集を解き直し、食い違った問題だけを報告する
+pub fn run(file: &str, timeout: Option<Duration>) -> Result<bool, ParseError> {
+    let mut text = String::new();
+    if file == "-" {
+        std::io::stdin().lock().read_to_string(&mut text)?;
+    } else {
+        text = std::fs::read_to_string(file)?;
+    }
+    let (mut total, mut failed) = (0, 0);
+    for (i, line) in text.lines().enumerate() {
+        if line.trim().is_empty() || line.starts_with('#') {
+            continue;
+        }
+        total += 1;
+        let result = line
+            .parse::<RegressionCase>()
+            .map_err(|e| e.to_string())
+            .and_then(|case| {
+                verify_case::<YasaiPosition, HashMapTable>(&case, timeout)
+                    .map_err(|e| e.to_string())
+            });
+        if let Err(e) = result {
+            failed += 1;
+            let sfen = line.split('\t').next().unwrap_or_default();
+            println!("{file}:{}: {sfen}: {e}", i + 1);
+        }
+    }
+    println!("{total} problems, {failed} mismatches");
+    Ok(failed == 0)
+}
@@ -0,0 +1,191 @@
+use clap::ArgEnum;
+use serde::Serialize;
+use shogi_core::{Move, PartialPosition, ToUsi};
+use solver::CanceledError;
+#[cfg(feature = "stats")]
+use std::fmt;
+use std::time::Duration;
+use tsumeshogi_solver::format::{output, OutputFormat};
+use tsumeshogi_solver::parse::ParseError;
+
+#[derive(Clone, Copy, PartialEq, Eq, ArgEnum)]
+pub enum Format {
+    Text,
+    Json,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Status {
+    Mate,
+    NoMate,
+    Timeout,
+    NodeLimit,
+    Canceled,
+    Error,
+}
+
+#[derive(Serialize)]
+pub struct Moves {
+    pub usi: Vec<String>,
+    pub csa: Vec<String>,
+    pub kifu: Vec<String>,
+}
+
+// `stats` feature を有効にしてビルドしたときの探索の統計
+#[cfg(feature = "stats")]
+#[derive(Serialize)]
+pub struct Stats {
+    pub expansions: u64,
+    pub max_depth: usize,
+    pub or_branching: f64,
+    pub and_branching: f64,
+    pub lookups: u64,
+    pub hits: u64,
+    pub misses: u64,
+    pub overwrites: u64,
+    pub gc_runs: u64,
+}
+
+#[cfg(feature = "stats")]
+impl From<solver::Stats> for Stats {
+    fn from(stats: solver::Stats) -> Self {
+        Self {
+            expansions: stats.search.expansions,
+            max_depth: stats.search.max_depth,
+            or_branching: stats.search.or_branching(),
+            and_branching: stats.search.and_branching(),
+            lookups: stats.table.lookups,
+            hits: stats.table.hits,
+            misses: stats.table.misses,
+            overwrites: stats.table.overwrites,
+            gc_runs: stats.table.gc_runs,
+        }
+    }
+}
+
+#[cfg(feature = "stats")]
+impl fmt::Display for Stats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "expansions: {}, max depth: {}, branching: {:.2} (or) / {:.2} (and), lookups: {} (hits: {}, misses: {}), overwrites: {}, gc runs: {}",
+            self.expansions,
+            self.max_depth,
+            self.or_branching,
+            self.and_branching,
+            self.lookups,
+            self.hits,
+            self.misses,
+            self.overwrites,
+            self.gc_runs
+        )
+    }
+}
+
+// `--format json` で 1 入力 (1 局面) ごとに 1 行出力する
+#[derive(Serialize)]
+pub struct Report {
+    pub input: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ply: Option<usize>,
+    pub sfen: Option<String>,
+    pub status: Status,
+    pub moves: Option<Moves>,
+    pub length: Option<usize>,
+    pub nodes: Option<u64>,
+    pub elapsed: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub played: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub found: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub remaining: Option<usize>,
+    #[cfg(feature = "stats")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stats: Option<Stats>,
+    pub error: Option<String>,
+}
+
+impl Report {
+    pub fn new(
+        input: &str,
+        pos: &PartialPosition,
+        result: &Result<Vec<Move>, CanceledError>,
+        nodes: u64,
+        elapsed: Duration,
+    ) -> Self {
+        let sfen = pos.to_usi_owned();
+        let mut report = Self {
+            input: input.to_string(),
+            ply: None,
+            sfen: Some(sfen.strip_prefix("sfen ").unwrap_or(&sfen).to_string()),
+            status: Status::Error,
+            moves: None,
+            length: None,
+            nodes: Some(nodes),
+            elapsed: Some(elapsed.as_secs_f64()),
+            played: None,
+            found: None,
+            remaining: None,
+            #[cfg(feature = "stats")]
+            stats: None,
+            error: None,
+        };
+        match result {
+            Ok(v) => {
+                report.status = if v.is_empty() {
+                    Status::NoMate
+                } else {
+                    Status::Mate
+                };
+                report.moves = Some(Moves {
+                    usi: output(pos, v.clone(), OutputFormat::Usi),
+                    csa: output(pos, v.clone(), OutputFormat::Csa),
+                    kifu: output(pos, v.clone(), OutputFormat::Kifu),
+                });
+                report.length = Some(v.len());
+            }
+            Err(e) => {
+                report.status = match e {
+                    CanceledError::Timeout => Status::Timeout,
+                    CanceledError::NodeLimit => Status::NodeLimit,
+                    CanceledError::Canceled => Status::Canceled,
+                };
+                report.error = Some(e.to_string());
+            }
+        }
+        report
+    }
+    pub fn error(input: &str, err: &ParseError) -> Self {
+        Self {
+            input: input.to_string(),
+            ply: None,
+            sfen: None,
+            status: Status::Error,
+            moves: None,
+            length: None,
+            nodes: None,
+            elapsed: None,
+            played: None,
+            found: None,
+            remaining: None,
+            #[cfg(feature = "stats")]
+            stats: None,
+            error: Some(err.to_string()),
+        }
+    }
+    // ヒントとして最初の plies 手だけを残し、詰みまでの残り手数を添える
+    pub fn truncate(&mut self, plies: usize) {
+        if let (Some(moves), Some(length)) = (&mut self.moves, self.length) {
+            let len = plies.min(length);
+            moves.usi.truncate(len);
+            moves.csa.truncate(len);
+            moves.kifu.truncate(len);
+            self.remaining = Some(length - len);
+        }
+    }
+    pub fn to_json(&self) -> Result<String, ParseError> {
+        Ok(serde_json::to_string(self)?)
+    }
+}
@@ -0,0 +1,331 @@
+use crate::report::Report;
+use crate::Searcher;
+use serde::Deserialize;
+use shogi_core::PartialPosition;
+use solver::implementations::YasaiPosition;
+use solver::{best_solution, CanceledError};
+use std::io::{BufRead, BufReader, ErrorKind, Read, Write};
+use std::net::{Ipv4Addr, Shutdown, TcpListener, TcpStream};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+use tsumeshogi_solver::parse::{
+    detect, parse_sfen, parser, position_at, InputEncoding, InputFormat, ParseError,
+};
+
+const READ_TIMEOUT: Duration = Duration::from_secs(10);
+// Content-Length がこれを超えるリクエストは読まずに 413 を返す
+const MAX_BODY: usize = 64 * 1024;
+// 探索中に接続が切れていないかを確かめる間隔
+const PEEK_INTERVAL: Duration = Duration::from_millis(100);
+
+#[derive(Deserialize)]
+struct SolveRequest {
+    #[serde(default)]
+    id: String,
+    position: String,
+    format: Option<String>,
+    // 秒。キューで待った時間も含めて、これを過ぎたら打ち切る
+    timeout: Option<f32>,
+    #[serde(default)]
+    options: SolveOptions,
+}
+
+#[derive(Default, Deserialize)]
+struct SolveOptions {
+    ply: Option<usize>,
+    hint: Option<usize>,
+}
+
+// ループバックのみで待ち受け、受け付けた接続を jobs 個の worker で順に処理する
+pub fn run(port: u16, jobs: usize, timeout: Option<Duration>) -> Result<(), ParseError> {
+    let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, port))?;
+    eprintln!("listening on http://{}", listener.local_addr()?);
+    serve(listener, jobs, timeout);
+    Ok(())
+}
+
+fn serve(listener: TcpListener, jobs: usize, timeout: Option<Duration>) {
+    let (tx, rx) = mpsc::channel::<(TcpStream, Instant)>();
+    let rx = Arc::new(Mutex::new(rx));
+    for _ in 0..jobs.max(1) {
+        let rx = Arc::clone(&rx);
+        thread::spawn(move || loop {
+            let Ok(Ok((stream, accepted))) = rx.lock().map(|rx| rx.recv()) else {
+                break;
+            };
+            if let Err(e) = handle(stream, accepted, timeout) {
+                eprintln!("{e}");
+            }
+        });
+    }
+    for stream in listener.incoming() {
+        match stream {
+            Ok(stream) => {
+                if tx.send((stream, Instant::now())).is_err() {
+                    break;
+                }
+            }
+            Err(e) => eprintln!("{e}"),
+        }
+    }
+}
+
+fn handle(stream: TcpStream, accepted: Instant, timeout: Option<Duration>) -> std::io::Result<()> {
+    stream.set_read_timeout(Some(READ_TIMEOUT))?;
+    let mut reader = BufReader::new(&stream);
+    let mut line = String::new();
+    reader.read_line(&mut line)?;
+    let mut parts = line.split_whitespace();
+    let (method, path) = (
+        parts.next().unwrap_or_default(),
+        parts.next().unwrap_or_default(),
+    );
+    let mut length = 0;
+    loop {
+        let mut header = String::new();
+        if reader.read_line(&mut header)? == 0 || header.trim().is_empty() {
+            break;
+        }
+        if let Some((key, value)) = header.split_once(':') {
+            if key.trim().eq_ignore_ascii_case("content-length") {
+                length = value.trim().parse().unwrap_or_default();
+            }
+        }
+    }
+    let (status, body) = match (method, path) {
+        _ if length > MAX_BODY => (
+            "413 Payload Too Large",
+            format!(r#"{{"error":"request body exceeds {MAX_BODY} bytes"}}"#),
+        ),
+        ("POST", "/solve") => {
+            let mut body = vec![0; length];
+            reader.read_exact(&mut body)?;
+            let cancel_flag = Arc::new(AtomicBool::new(false));
+            let done = Arc::new(AtomicBool::new(false));
+            let watcher = watch(stream.try_clone()?, &cancel_flag, &done);
+            let response = solve(&body, accepted, timeout, cancel_flag);
+            // 読み込み側を閉じると peek がすぐに戻るので、watcher を待たずに済む
+            done.store(true, Ordering::Relaxed);
+            stream.shutdown(Shutdown::Read).ok();
+            watcher.join().ok();
+            response
+        }
+        (_, "/solve") => (
+            "405 Method Not Allowed",
+            String::from(r#"{"error":"use POST"}"#),
+        ),
+        _ => ("404 Not Found", String::from(r#"{"error":"not found"}"#)),
+    };
+    let mut stream = &stream;
+    write!(
+        stream,
+        "HTTP/1.1 {status}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+        body.len()
+    )?;
+    stream.flush()
+}
+
+// 探索の間、接続が閉じられるか peek が失敗したら cancel_flag を立てて worker を解放する。
+// リクエストを読み終えた後なので、クライアントが送ってくるのは EOF かエラーだけのはず
+fn watch(
+    stream: TcpStream,
+    cancel_flag: &Arc<AtomicBool>,
+    done: &Arc<AtomicBool>,
+) -> thread::JoinHandle<()> {
+    let (cancel_flag, done) = (Arc::clone(cancel_flag), Arc::clone(done));
+    thread::spawn(move || {
+        if stream.set_read_timeout(Some(PEEK_INTERVAL)).is_err() {
+            return;
+        }
+        let mut buf = [0; 1];
+        while !done.load(Ordering::Relaxed) {
+            match stream.peek(&mut buf) {
+                Ok(0) => break,
+                Ok(_) => thread::sleep(PEEK_INTERVAL),
+                Err(e) if matches!(e.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) => {}
+                Err(_) => break,
+            }
+        }
+        if !done.load(Ordering::Relaxed) {
+            cancel_flag.store(true, Ordering::Relaxed);
+        }
+    })
+}
+
+fn solve(
+    body: &[u8],
+    accepted: Instant,
+    timeout: Option<Duration>,
+    cancel_flag: Arc<AtomicBool>,
+) -> (&'static str, String) {
+    let request = match serde_json::from_slice::<SolveRequest>(body) {
+        Ok(request) => request,
+        Err(e) => return bad_request("", &e.into()),
+    };
+    let pos = match position(&request) {
+        Ok(pos) => pos,
+        Err(e) => return bad_request(&request.id, &e),
+    };
+    let timeout = match request.timeout.map(Duration::try_from_secs_f32).transpose() {
+        Ok(t) => t.or(timeout),
+        Err(_) => {
+            let timeout = request.timeout.unwrap_or_default().to_string();
+            return bad_request(&request.id, &ParseError::InvalidTimeout(timeout));
+        }
+    };
+    let now = Instant::now();
+    let (result, nodes) = match timeout.map(|t| t.saturating_sub(accepted.elapsed())) {
+        Some(Duration::ZERO) => (Err(CanceledError::Timeout), 0),
+        remaining => {
+            let mut searcher = Searcher::new(YasaiPosition::from(pos.clone()), remaining);
+            searcher.set_cancel_flag(cancel_flag);
+            let result = searcher.dfpn_search().map(|_| best_solution(&mut searcher));
+            (result, searcher.nodes())
+        }
+    };
+    let mut report = Report::new(&request.id, &pos, &result, nodes, now.elapsed());
+    if let Some(plies) = request.options.hint {
+        report.truncate(plies);
+    }
+    match report.to_json() {
+        Ok(json) => ("200 OK", json),
+        Err(e) => bad_request(&request.id, &e),
+    }
+}
+
+fn bad_request(id: &str, err: &ParseError) -> (&'static str, String) {
+    let json = Report::error(id, err).to_json().unwrap_or_default();
+    ("400 Bad Request", json)
+}
+
+fn position(request: &SolveRequest) -> Result<PartialPosition, ParseError> {
+    let input = request.position.as_bytes();
+    let format = match request.format.as_deref() {
+        Some(s) => s.parse()?,
+        None => InputFormat::Auto,
+    };
+    let format = match format {
+        InputFormat::Auto => detect(None, input, InputEncoding::Auto)
+            .ok_or_else(|| ParseError::UnknownFormat(request.position.clone()))?,
+        format => format,
+    };
+    // SFEN は指し手適用後、棋譜は開始局面を既定で解く
+    let (record, last) = match format {
+        InputFormat::Sfen => (parse_sfen(&request.position)?, true),
+        format => {
+            let mut records = parser(format, InputEncoding::Auto)?.parse(input)?;
+            if records.is_empty() {
+                return Err(ParseError::UnknownFormat(request.position.clone()));
+            }
+            (records.swap_remove(0).position, false)
+        }
+    };
+    let len = record.moves().len();
+    let ply = request.options.ply.unwrap_or(if last { len } else { 0 });
+    position_at(&record, ply).ok_or(ParseError::PlyOutOfRange(ply, len))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(addr: std::net::SocketAddr, request: &str) -> String {
+        let mut stream = TcpStream::connect(addr).expect("failed to connect");
+        stream
+            .write_all(request.as_bytes())
+            .expect("failed to send");
+        let mut response = String::new();
+        stream
+            .read_to_string(&mut response)
+            .expect("failed to receive");
+        response
+    }
+
+    #[test]
+    fn loopback() {
+        let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0)).expect("failed to bind");
+        let addr = listener.local_addr().expect("failed to get address");
+        thread::spawn(move || serve(listener, 2, Some(Duration::from_secs(5))));
+
+        let body = r#"{"id":"readme","position":"9/9/3pp4/+r2k1p3/2L1+p4/2+R6/B8/B8/9 b 4g4s4n3l14p 1","timeout":5}"#;
+        let response = request(
+            addr,
+            &format!(
+                "POST /solve HTTP/1.1\r\nHost: localhost\r\nContent-Length: {}\r\n\r\n{body}",
+                body.len()
+            ),
+        );
+        assert!(response.starts_with("HTTP/1.1 200 OK"), "{response}");
+        assert!(response.contains(r#""status":"mate""#), "{response}");
+        assert!(
+            response.contains(r#""usi":["7e7b+","N*8f","7f7c"]"#),
+            "{response}"
+        );
+
+        let body = r#"{"position":"invalid"}"#;
+        let response = request(
+            addr,
+            &format!(
+                "POST /solve HTTP/1.1\r\nContent-Length: {}\r\n\r\n{body}",
+                body.len()
+            ),
+        );
+        assert!(response.starts_with("HTTP/1.1 400"), "{response}");
+        assert!(response.contains(r#""status":"error""#), "{response}");
+
+        let body =
+            r#"{"position":"9/9/3pp4/+r2k1p3/2L1+p4/2+R6/B8/B8/9 b 4g4s4n3l14p 1","timeout":-1}"#;
+        let response = request(
+            addr,
+            &format!(
+                "POST /solve HTTP/1.1\r\nContent-Length: {}\r\n\r\n{body}",
+                body.len()
+            ),
+        );
+        assert!(response.starts_with("HTTP/1.1 400"), "{response}");
+
+        let response = request(
+            addr,
+            "POST /solve HTTP/1.1\r\nContent-Length: 1000000000\r\n\r\n",
+        );
+        assert!(response.starts_with("HTTP/1.1 413"), "{response}");
+
+        let response = request(addr, "GET / HTTP/1.1\r\n\r\n");
+        assert!(response.starts_with("HTTP/1.1 404"), "{response}");
+    }
+
+    #[test]
+    fn cancel_on_disconnect() {
+        // worker が 1 つで時間制限もないので、切断で探索が止まらなければ次のリクエストは返らない
+        let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0)).expect("failed to bind");
+        let addr = listener.local_addr().expect("failed to get address");
+        thread::spawn(move || serve(listener, 1, None));
+
+        // solve_mates で時間がかかりすぎて外している 7手詰
+        let body = r#"{"position":"ln3kgRl/2s1g2p1/2ppppn1p/p5p2/6b2/P3P4/1+rPP1PP1P/1P4S2/LNSK1G1NL w GPbsp 50"}"#;
+        let mut stream = TcpStream::connect(addr).expect("failed to connect");
+        write!(
+            stream,
+            "POST /solve HTTP/1.1\r\nContent-Length: {}\r\n\r\n{body}",
+            body.len()
+        )
+        .expect("failed to send");
+        thread::sleep(Duration::from_millis(500));
+        drop(stream);
+
+        let mut stream = TcpStream::connect(addr).expect("failed to connect");
+        stream
+            .set_read_timeout(Some(Duration::from_secs(10)))
+            .expect("failed to set timeout");
+        stream
+            .write_all(b"GET / HTTP/1.1\r\n\r\n")
+            .expect("failed to send");
+        let mut response = String::new();
+        stream
+            .read_to_string(&mut response)
+            .expect("worker was not released");
+        assert!(response.starts_with("HTTP/1.1 404"), "{response}");
+    }
+}
@@ -0,0 +1,125 @@
+use crate::format::{output, OutputFormat};
+use crate::Problem;
+use dfpn::Table;
+use shogi_core::{Move, PartialPosition};
+use solver::implementations::{HashMapTable, VecTable, YasaiPosition};
+use solver::{best_solution_with_rules, CancelableSearcher, CanceledError, Rules};
+use std::time::{Duration, Instant};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum TableKind {
+    #[default]
+    HashMap,
+    // 固定長で上書きされるため省メモリだが、長手数の問題では解けないことがある
+    Vec,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct SolverBuilder {
+    table: TableKind,
+    timeout: Option<Duration>,
+    node_limit: Option<u64>,
+    rules: Rules,
+}
+
+impl SolverBuilder {
+    pub fn table(mut self, table: TableKind) -> Self {
+        self.table = table;
+        self
+    }
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+    pub fn node_limit(mut self, node_limit: u64) -> Self {
+        self.node_limit = Some(node_limit);
+        self
+    }
+    pub fn rules(mut self, rules: Rules) -> Self {
+        self.rules = rules;
+        self
+    }
+    pub fn build(self) -> Solver {
+        Solver(self)
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct Solver(SolverBuilder);
+
+impl Solver {
+    pub fn builder() -> SolverBuilder {
+        SolverBuilder::default()
+    }
+    pub fn solve(&self, problem: &Problem) -> Result<Solution, CanceledError> {
+        match self.0.table {
+            TableKind::HashMap => self.solve_with::<HashMapTable>(&problem.position),
+            TableKind::Vec => self.solve_with::<VecTable>(&problem.position),
+        }
+    }
+    fn solve_with<T: Table>(&self, position: &PartialPosition) -> Result<Solution, CanceledError> {
+        let now = Instant::now();
+        let mut searcher: CancelableSearcher<YasaiPosition, T> =
+            CancelableSearcher::new(YasaiPosition::from(position.clone()), self.0.timeout);
+        searcher.set_node_limit(self.0.node_limit);
+        searcher.dfpn_search()?;
+        Ok(Solution {
+            position: position.clone(),
+            moves: best_solution_with_rules(&mut searcher, self.0.rules),
+            nodes: searcher.nodes(),
+            elapsed: now.elapsed(),
+        })
+    }
+}
+
+// 手順が空なら不詰
+#[derive(Clone, Debug)]
+pub struct Solution {
+    pub position: PartialPosition,
+    pub moves: Vec<Move>,
+    pub nodes: u64,
+    pub elapsed: Duration,
+}
+
+impl Solution {
+    pub fn is_mate(&self) -> bool {
+        !self.moves.is_empty()
+    }
+    pub fn format(&self, format: OutputFormat) -> Vec<String> {
+        output(&self.position, self.moves.clone(), format)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builder() {
+        let problem = "9/9/3pp4/+r2k1p3/2L1+p4/2+R6/B8/B8/9 b 4g4s4n3l14p 1"
+            .parse::<Problem>()
+            .expect("failed to parse");
+        for table in [TableKind::HashMap, TableKind::Vec] {
+            let solver = Solver::builder()
+                .table(table)
+                .timeout(Duration::from_secs(5))
+                .build();
+            match solver.solve(&problem) {
+                Ok(solution) => {
+                    assert_eq!(
+                        solution.format(OutputFormat::Usi),
+                        ["7e7b+", "N*8f", "7f7c"]
+                    );
+                }
+                Err(e) => {
+                    panic!("canceled {table:?}: {e}");
+                }
+            }
+        }
+        let solver = Solver::builder().node_limit(1).build();
+        assert!(matches!(
+            solver.solve(&problem),
+            Err(CanceledError::NodeLimit)
+        ));
+    }
+}
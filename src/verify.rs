@@ -0,0 +1,77 @@
+use solver::implementations::{HashMapTable, YasaiPosition};
+use solver::{verify_solution, FlawKind};
+use std::path::Path;
+use std::time::Duration;
+use tsumeshogi_solver::certify::certify_json;
+use tsumeshogi_solver::format::{output, OutputFormat};
+use tsumeshogi_solver::parse::{parse_moves, parse_sfen, position_at, ParseError};
+
+// 主張された手順を検証し、最初に破綻する手とその反証を出力する
+pub fn run(
+    position: &str,
+    moves: &[String],
+    timeout: Option<Duration>,
+    format: OutputFormat,
+) -> Result<bool, ParseError> {
+    let record = parse_sfen(position)?;
+    let len = record.moves().len();
+    let pos = position_at(&record, len).ok_or(ParseError::PlyOutOfRange(len, len))?;
+    let moves = parse_moves(&pos, &moves.join(" "))?;
+    if moves.is_empty() {
+        println!("{}", FlawKind::NoMoves);
+        return Ok(false);
+    }
+    match verify_solution::<YasaiPosition, HashMapTable>(&pos, &moves, timeout) {
+        Ok(None) => {
+            println!("ok: mate in {}", moves.len());
+            Ok(true)
+        }
+        Ok(Some(flaw)) => {
+            let mut before = pos.clone();
+            for &m in &moves[..flaw.ply - 1] {
+                before.make_move(m);
+            }
+            let played = output(&pos, moves[..flaw.ply].to_vec(), format);
+            println!(
+                "ply {} ({}): {}",
+                flaw.ply,
+                played.last().map_or("", String::as_str),
+                flaw.kind
+            );
+            if !flaw.refutation.is_empty() {
+                // 余詰と最長でない応手はその手の代わり、それ以外は破綻した手の直後からの手順
+                let mut at = before;
+                if !matches!(flaw.kind, FlawKind::Alternative | FlawKind::LongerDefence) {
+                    at.make_move(moves[flaw.ply - 1]);
+                }
+                println!(
+                    "refutation: {}",
+                    output(&at, flaw.refutation, format).join(" ")
+                );
+            }
+            Ok(false)
+        }
+        Err(e) => {
+            println!("{e}");
+            Ok(false)
+        }
+    }
+}
+
+// 証明木の検証は yasai も置換表も使わないので、探索のバグで誤って「詰」と答えていないかを確かめられる
+pub fn check_tree(position: &str, file: &Path) -> Result<bool, ParseError> {
+    let record = parse_sfen(position)?;
+    let len = record.moves().len();
+    let pos = position_at(&record, len).ok_or(ParseError::PlyOutOfRange(len, len))?;
+    let tree = serde_json::from_str(&std::fs::read_to_string(file)?)?;
+    match certify_json(&pos, &tree) {
+        Ok(len) => {
+            println!("ok: mate in {len} (certified)");
+            Ok(true)
+        }
+        Err(e) => {
+            println!("{e}");
+            Ok(false)
+        }
+    }
+}
@@ -0,0 +1,158 @@
+use shogi_core::{Move, PartialPosition, ToUsi};
+use shogi_usi_parser::FromUsi;
+use solver::implementations::{HashMapTable, YasaiPosition};
+use solver::{best_solution, CancelableSearcher, CanceledError};
+use std::time::Duration;
+use wasm_bindgen::prelude::*;
+
+type Searcher = CancelableSearcher<YasaiPosition, HashMapTable>;
+
+#[wasm_bindgen]
+pub struct SolveResult {
+    status: &'static str,
+    moves: Vec<Move>,
+    nodes: u64,
+}
+
+#[wasm_bindgen]
+impl SolveResult {
+    // "mate", "nomate", "timeout", "nodelimit", "canceled"
+    #[wasm_bindgen(getter)]
+    pub fn status(&self) -> String {
+        self.status.to_string()
+    }
+    #[wasm_bindgen(getter)]
+    pub fn moves(&self) -> Vec<String> {
+        self.moves.iter().map(|m| m.to_usi_owned()).collect()
+    }
+    #[wasm_bindgen(getter)]
+    pub fn nodes(&self) -> f64 {
+        self.nodes as f64
+    }
+}
+
+impl SolveResult {
+    fn new(result: Result<Vec<Move>, CanceledError>, nodes: u64) -> Self {
+        let (status, moves) = match result {
+            Ok(v) if v.is_empty() => ("nomate", v),
+            Ok(v) => ("mate", v),
+            Err(CanceledError::Timeout) => ("timeout", Vec::new()),
+            Err(CanceledError::NodeLimit) => ("nodelimit", Vec::new()),
+            Err(CanceledError::Canceled) => ("canceled", Vec::new()),
+        };
+        Self {
+            status,
+            moves,
+            nodes,
+        }
+    }
+}
+
+fn searcher(sfen: &str, timeout: Option<Duration>) -> Result<Searcher, JsError> {
+    let sfen = sfen.trim();
+    let sfen = sfen.strip_prefix("sfen").map_or(sfen, str::trim_start);
+    let pos = PartialPosition::from_usi(&format!("sfen {sfen}"))
+        .map_err(|_| JsError::new(&format!("invalid sfen: {sfen}")))?;
+    Ok(Searcher::new(YasaiPosition::from(pos), timeout))
+}
+
+// Infinity や NaN は時間制限なし、負の値はエラー。JsError は wasm 以外では作れないので String で返す
+fn parse_timeout(timeout_ms: Option<f64>) -> Result<Option<Duration>, String> {
+    match timeout_ms.filter(|ms| ms.is_finite()) {
+        Some(ms) => Duration::try_from_secs_f64(ms / 1000.0)
+            .map(Some)
+            .map_err(|_| format!("invalid timeout: {ms}")),
+        None => Ok(None),
+    }
+}
+
+// 探索が終わるまで制御を返さない。UI スレッドでは start / step を使う
+#[wasm_bindgen]
+pub fn solve(sfen: &str, timeout_ms: Option<f64>) -> Result<SolveResult, JsError> {
+    let timeout = parse_timeout(timeout_ms).map_err(|e| JsError::new(&e))?;
+    let mut searcher = searcher(sfen, timeout)?;
+    let result = searcher.dfpn_search().map(|_| best_solution(&mut searcher));
+    Ok(SolveResult::new(result, searcher.nodes()))
+}
+
+#[wasm_bindgen]
+pub fn start(sfen: &str) -> Result<Search, JsError> {
+    Ok(Search {
+        searcher: searcher(sfen, None)?,
+        result: None,
+    })
+}
+
+// step ごとに指定した局面数だけ探索し、置換表を保ったまま呼び出し元に制御を返す
+#[wasm_bindgen]
+pub struct Search {
+    searcher: Searcher,
+    result: Option<SolveResult>,
+}
+
+#[wasm_bindgen]
+impl Search {
+    // 探索が終わっていれば true
+    pub fn step(&mut self, nodes: f64) -> bool {
+        if self.result.is_some() {
+            return true;
+        }
+        self.searcher
+            .set_node_limit(Some(self.searcher.nodes() + nodes.max(1.0) as u64));
+        match self.searcher.dfpn_search() {
+            Err(CanceledError::NodeLimit) => false,
+            result => {
+                let result = result.map(|_| best_solution(&mut self.searcher));
+                self.result = Some(SolveResult::new(result, self.searcher.nodes()));
+                true
+            }
+        }
+    }
+    pub fn result(&mut self) -> Option<SolveResult> {
+        self.result.take()
+    }
+    #[wasm_bindgen(getter)]
+    pub fn nodes(&self) -> f64 {
+        self.searcher.nodes() as f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PROBLEM: &str = "9/9/3pp4/+r2k1p3/2L1+p4/2+R6/B8/B8/9 b 4g4s4n3l14p 1";
+
+    #[test]
+    fn solve_at_once() {
+        let result = solve(PROBLEM, Some(5000.0)).unwrap_or_else(|_| panic!("invalid sfen"));
+        assert_eq!(result.status(), "mate");
+        assert_eq!(result.moves(), ["7e7b+", "N*8f", "7f7c"]);
+    }
+
+    #[test]
+    fn timeouts() {
+        assert_eq!(
+            parse_timeout(Some(1500.0)),
+            Ok(Some(Duration::from_millis(1500)))
+        );
+        assert_eq!(parse_timeout(Some(f64::INFINITY)), Ok(None));
+        assert_eq!(parse_timeout(Some(f64::NAN)), Ok(None));
+        assert_eq!(parse_timeout(None), Ok(None));
+        assert!(parse_timeout(Some(-1.0)).is_err());
+    }
+
+    #[test]
+    fn step_by_step() {
+        let mut search = start(PROBLEM).unwrap_or_else(|_| panic!("invalid sfen"));
+        let mut steps = 0;
+        while !search.step(10.0) {
+            steps += 1;
+            assert!(steps < 100_000, "not finished");
+        }
+        assert!(steps > 0);
+        let result = search.result().expect("no result");
+        assert_eq!(result.status(), "mate");
+        assert_eq!(result.moves(), ["7e7b+", "N*8f", "7f7c"]);
+    }
+}